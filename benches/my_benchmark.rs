@@ -1,5 +1,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 use generativity::make_guard;
+use generativity_pattern_rs::branded_arena::BrandedArena;
+use generativity_pattern_rs::branded_vec::BrandedVec;
 use generativity_pattern_rs::{mod_3_unsafe_trait::ComposablePermutation, *};
 use std::hint::black_box;
 
@@ -47,6 +49,15 @@ pub fn bench_compose_permutations(c: &mut Criterion) {
         let mut into = perm_a.compose(perm_b).unwrap();
         b.iter(|| black_box(perm_a).compose_into(black_box(perm_b), black_box(&mut into)))
     });
+    group.bench_function("4-atomic_id (compact)", |b| {
+        let perm_group =
+            mod_4_atomic_id::PermGroup::new_compact(15, vec![PERM_A.to_vec(), PERM_B.to_vec()])
+                .unwrap();
+        let perm_a = &perm_group.base_permutations()[0];
+        let perm_b = &perm_group.base_permutations()[1];
+        let mut into = perm_a.compose(perm_b).unwrap();
+        b.iter(|| black_box(perm_a).compose_into(black_box(perm_b), black_box(&mut into)))
+    });
     group.bench_function("5-generativity", |b| {
         make_guard!(guard);
         let perm_group =
@@ -70,5 +81,56 @@ pub fn bench_compose_permutations(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_compose_permutations);
+const ARENA_LEN: usize = 1000;
+
+pub fn bench_arena_access(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Arena access");
+    let values: Vec<u64> = (0..ARENA_LEN as u64).collect();
+    // A fixed shuffle, so that lookups are not sequential.
+    let order: Vec<usize> = (0..ARENA_LEN).map(|i| i * 7919 % ARENA_LEN).collect();
+
+    group.bench_function("Vec", |b| {
+        b.iter(|| order.iter().map(|&i| black_box(&values)[i]).sum::<u64>())
+    });
+    group.bench_function("BrandedVec", |b| {
+        make_guard!(guard);
+        let vec = BrandedVec::new(values.clone(), guard);
+        let indices: Vec<_> = order.iter().map(|&i| vec.get_index(i).unwrap()).collect();
+        b.iter(|| {
+            indices
+                .iter()
+                .map(|&i| *black_box(&vec).get(i))
+                .sum::<u64>()
+        })
+    });
+    group.bench_function("BrandedArena", |b| {
+        make_guard!(guard);
+        let mut arena = BrandedArena::with_capacity(ARENA_LEN, guard);
+        let handles: Vec<_> = values.iter().map(|&value| arena.alloc(value)).collect();
+        let handles: Vec<_> = order.iter().map(|&i| handles[i]).collect();
+        b.iter(|| {
+            handles
+                .iter()
+                .map(|&i| *black_box(&arena).get(i))
+                .sum::<u64>()
+        })
+    });
+    group.bench_function("GenerationalArena", |b| {
+        make_guard!(guard);
+        let mut arena = BrandedArena::with_capacity(ARENA_LEN, guard);
+        let handles: Vec<_> = values.iter().map(|&value| arena.alloc(value)).collect();
+        let arena = arena.into_generational();
+        let handles: Vec<_> = order.iter().map(|&i| arena.handle(handles[i])).collect();
+        b.iter(|| {
+            handles
+                .iter()
+                .map(|&i| *black_box(&arena).get(i).unwrap())
+                .sum::<u64>()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_compose_permutations, bench_arena_access);
 criterion_main!(benches);
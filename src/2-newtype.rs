@@ -26,3 +26,20 @@ impl Permutation {
         Ok(result)
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Permutation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserializing goes through `Permutation::from_mapping`, so a malformed
+/// mapping is rejected rather than reaching `compose_into`'s unchecked
+/// indexing.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Permutation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::from_mapping(Vec::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
@@ -1,76 +1,317 @@
 use crate::{validate_permutation, validate_permutation_group_membership};
-use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering::Relaxed};
 
-pub struct PermGroup {
+pub struct PermGroup<I: GroupId = u64> {
     base_permutation_length: usize,
-    base_permutations: Vec<Permutation>,
-    id: u64,
+    base_permutations: Vec<Permutation<I>>,
+    id: I,
+}
+
+/// An ID type that can tag a `PermGroup` and its permutations.
+///
+/// # Safety
+///
+/// Every call to `allocate` must return an ID that has never been
+/// returned before, or fail. `Permutation::compose_into` skips bounds
+/// checks for permutations with equal IDs, so a reused ID would let
+/// permutations of different lengths be composed.
+pub unsafe trait GroupId: Copy + Eq {
+    fn allocate() -> Result<Self, &'static str>;
 }
 
 static ID: AtomicU64 = AtomicU64::new(0);
 
+fn allocate_from(counter: &AtomicU64) -> Result<u64, &'static str> {
+    counter
+        .fetch_update(Relaxed, Relaxed, |id| id.checked_add(1))
+        .map_err(|_| "Permutation group IDs are exhausted")
+}
+
+// SAFETY: `allocate_from` fails instead of wrapping around.
+unsafe impl GroupId for u64 {
+    fn allocate() -> Result<Self, &'static str> {
+        allocate_from(&ID)
+    }
+}
+
+static COMPACT_ID_BLOCK: AtomicU32 = AtomicU32::new(0);
+
+/// How many compact IDs a thread reserves from `COMPACT_ID_BLOCK` at
+/// once, so that most allocations don't touch the shared counter.
+const COMPACT_ID_BLOCK_SIZE: u32 = 1 << 10;
+
+thread_local! {
+    // The next ID to hand out and the end of the reserved block.
+    static COMPACT_IDS: Cell<(u32, u32)> = const { Cell::new((0, 0)) };
+}
+
+fn allocate_compact_from(
+    block_counter: &AtomicU32,
+    ids: &Cell<(u32, u32)>,
+) -> Result<u32, &'static str> {
+    let (mut next, mut end) = ids.get();
+    if next == end {
+        next = block_counter
+            .fetch_update(Relaxed, Relaxed, |start| {
+                start.checked_add(COMPACT_ID_BLOCK_SIZE)
+            })
+            .map_err(|_| "Permutation group IDs are exhausted")?;
+        end = next + COMPACT_ID_BLOCK_SIZE;
+    }
+    ids.set((next + 1, end));
+    Ok(next)
+}
+
+// SAFETY: Each block of IDs is reserved by one thread, and
+// `allocate_compact_from` fails instead of wrapping around.
+unsafe impl GroupId for u32 {
+    fn allocate() -> Result<Self, &'static str> {
+        COMPACT_IDS.with(|ids| allocate_compact_from(&COMPACT_ID_BLOCK, ids))
+    }
+}
+
 impl PermGroup {
+    /// Fails if a mapping is not a permutation of
+    /// `0..base_permutation_length`, or if that length does not fit in a
+    /// `u32`: every `Permutation` stores its length in 32 bits, whatever
+    /// its ID type.
     pub fn new(
         base_permutation_length: usize,
         base_permutation_mappings: Vec<Vec<usize>>,
+    ) -> Result<Self, &'static str> {
+        Self::with_id(base_permutation_length, base_permutation_mappings)
+    }
+}
+
+impl PermGroup<u32> {
+    /// Like `PermGroup::new`, but tags the group with a `u32` ID taken
+    /// from a per-thread block of IDs, which makes each `Permutation`
+    /// smaller.
+    pub fn new_compact(
+        base_permutation_length: usize,
+        base_permutation_mappings: Vec<Vec<usize>>,
+    ) -> Result<Self, &'static str> {
+        Self::with_id(base_permutation_length, base_permutation_mappings)
+    }
+}
+
+impl<I: GroupId> PermGroup<I> {
+    fn with_id(
+        base_permutation_length: usize,
+        base_permutation_mappings: Vec<Vec<usize>>,
     ) -> Result<Self, &'static str> {
         for mapping in &base_permutation_mappings {
             validate_permutation(mapping, base_permutation_length)?;
         }
-        let id = ID.fetch_add(1, Relaxed);
+        u32::try_from(base_permutation_length)
+            .map_err(|_| "Permutation length does not fit in 32 bits")?;
+        let id = I::allocate()?;
         Ok(Self {
             base_permutation_length,
             base_permutations: base_permutation_mappings
                 .into_iter()
-                .map(|mapping| Permutation(mapping.into_boxed_slice(), id))
+                .map(|mapping| Permutation::new(mapping.into_boxed_slice(), id))
                 .collect(),
             id,
         })
     }
 
-    pub fn base_permutations(&self) -> &[Permutation] {
+    pub fn base_permutations(&self) -> &[Permutation<I>] {
         &self.base_permutations
     }
 }
 
-pub struct Permutation(Box<[usize]>, u64);
+/// A `Box<[usize]>` split into a thin pointer and a `u32` length, so
+/// that a `u32` ID fits beside them in 16 bytes instead of 24.
+pub struct Permutation<I: GroupId = u64> {
+    mapping: NonNull<usize>,
+    len: u32,
+    id: I,
+}
+
+// SAFETY: A `Permutation` owns its mapping, like a `Box<[usize]>`.
+unsafe impl<I: GroupId + Send> Send for Permutation<I> {}
+// SAFETY: As for `Send`.
+unsafe impl<I: GroupId + Sync> Sync for Permutation<I> {}
+
+impl<I: GroupId> Drop for Permutation<I> {
+    fn drop(&mut self) {
+        let mapping = std::ptr::slice_from_raw_parts_mut(self.mapping.as_ptr(), self.len as usize);
+        // SAFETY: `mapping` and `len` came from `Box::into_raw` in `new`.
+        drop(unsafe { Box::from_raw(mapping) });
+    }
+}
+
+/// The serialized form of a `PermGroup`. Its ID is left out, since a
+/// deserialized group is a new group and gets a new ID.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PermGroupData<P> {
+    base_permutation_length: usize,
+    base_permutations: Vec<P>,
+}
+
+#[cfg(feature = "serde")]
+impl<I: GroupId> serde::Serialize for PermGroup<I> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PermGroupData {
+            base_permutation_length: self.base_permutation_length,
+            base_permutations: self
+                .base_permutations
+                .iter()
+                .map(Permutation::mapping)
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializing goes through the same validation as `PermGroup::new`
+/// and allocates a fresh ID. It is only implemented for this crate's own
+/// ID types, rather than trusting any `GroupId` implementation.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PermGroup<u64> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with_id(deserializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PermGroup<u32> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with_id(deserializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_with_id<'de, I: GroupId, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<PermGroup<I>, D::Error> {
+    use serde::Deserialize;
+    let data = PermGroupData::<Vec<usize>>::deserialize(deserializer)?;
+    PermGroup::with_id(data.base_permutation_length, data.base_permutations)
+        .map_err(serde::de::Error::custom)
+}
+
+impl<I: GroupId> Permutation<I> {
+    /// Takes ownership of `mapping`, whose length must fit in a `u32`.
+    fn new(mapping: Box<[usize]>, id: I) -> Self {
+        let len = mapping.len() as u32;
+        let mapping = Box::into_raw(mapping) as *mut usize;
+        Self {
+            // SAFETY: `Box::into_raw` never returns null.
+            mapping: unsafe { NonNull::new_unchecked(mapping) },
+            len,
+            id,
+        }
+    }
+
+    fn mapping(&self) -> &[usize] {
+        // SAFETY: As in `Drop`, this is the boxed slice from `new`.
+        unsafe { std::slice::from_raw_parts(self.mapping.as_ptr(), self.len as usize) }
+    }
+
+    fn mapping_mut(&mut self) -> &mut [usize] {
+        // SAFETY: As in `mapping`.
+        unsafe { std::slice::from_raw_parts_mut(self.mapping.as_ptr(), self.len as usize) }
+    }
 
-impl Permutation {
     pub fn from_mapping_and_group(
         mapping: Vec<usize>,
-        group: &PermGroup,
+        group: &PermGroup<I>,
     ) -> Result<Self, &'static str> {
         validate_permutation(&mapping, group.base_permutation_length)?;
-        let permutation = Self(mapping.into_boxed_slice(), group.id);
+        let permutation = Self::new(mapping.into_boxed_slice(), group.id);
         validate_permutation_group_membership(
-            &permutation.0,
+            permutation.mapping(),
             &group
                 .base_permutations
                 .iter()
-                .map(|p| &*p.0)
+                .map(Permutation::mapping)
                 .collect::<Vec<_>>(),
         )?;
         Ok(permutation)
     }
 
     pub fn compose_into(&self, b: &Self, result: &mut Self) -> Result<(), &'static str> {
-        if self.1 != b.1 || b.1 != result.1 {
+        if self.id != b.id || b.id != result.id {
             return Err("Permutations must come from the same permutation group");
         }
-        for i in 0..result.0.len() {
+        let (a, b) = (self.mapping(), b.mapping());
+        let result = result.mapping_mut();
+        for i in 0..result.len() {
             // SAFETY: `self`, `b`, and `into` have the same ID.
             // Therefore, they are members of the same group and
             // can be composed.
             unsafe {
-                *result.0.get_unchecked_mut(i) = *self.0.get_unchecked(*b.0.get_unchecked(i));
+                *result.get_unchecked_mut(i) = *a.get_unchecked(*b.get_unchecked(i));
             }
         }
         Ok(())
     }
 
     pub fn compose(&self, b: &Self) -> Result<Self, &'static str> {
-        let mut result = Self(vec![0; self.0.len()].into_boxed_slice(), self.1);
+        let mut result = Self::new(vec![0; self.len as usize].into_boxed_slice(), self.id);
         self.compose_into(b, &mut result)?;
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_do_not_wrap() {
+        let counter = AtomicU64::new(u64::MAX - 1);
+        assert_eq!(allocate_from(&counter), Ok(u64::MAX - 1));
+        assert!(allocate_from(&counter).is_err());
+        assert!(allocate_from(&counter).is_err());
+    }
+
+    #[test]
+    fn compact_ids_do_not_wrap() {
+        let last_block = u32::MAX - 2 * COMPACT_ID_BLOCK_SIZE + 1;
+        let block_counter = AtomicU32::new(last_block);
+        let ids = Cell::new((0, 0));
+        for i in 0..COMPACT_ID_BLOCK_SIZE {
+            assert_eq!(
+                allocate_compact_from(&block_counter, &ids),
+                Ok(last_block + i)
+            );
+        }
+        assert!(allocate_compact_from(&block_counter, &ids).is_err());
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn compact_permutations_are_smaller() {
+        assert_eq!(std::mem::size_of::<Permutation<u64>>(), 24);
+        assert_eq!(std::mem::size_of::<Permutation<u32>>(), 16);
+
+        let group = PermGroup::new_compact(3, vec![vec![1, 2, 0], vec![1, 0, 2]]).unwrap();
+        let [a, b] = &group.base_permutations[..] else {
+            unreachable!()
+        };
+        assert_eq!(a.compose(b).unwrap().mapping(), [2, 1, 0]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_revalidates() {
+        let group = PermGroup::new(3, vec![vec![1, 2, 0], vec![1, 0, 2]]).unwrap();
+        let json = serde_json::to_string(&group).unwrap();
+        let deserialized: PermGroup = serde_json::from_str(&json).unwrap();
+        assert_ne!(deserialized.id, group.id);
+        assert_eq!(
+            deserialized.base_permutations[1].mapping(),
+            group.base_permutations[1].mapping()
+        );
+
+        let malformed = r#"{"base_permutation_length":3,"base_permutations":[[1,2,3]]}"#;
+        assert!(serde_json::from_str::<PermGroup>(malformed).is_err());
+        assert!(serde_json::from_str::<crate::mod_2_newtype::Permutation>("[0,0]").is_err());
+    }
+}
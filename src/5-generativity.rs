@@ -1,10 +1,19 @@
 use crate::{validate_permutation, validate_permutation_group_membership};
 use generativity::{Guard, Id};
+use std::num::NonZeroUsize;
 
+/// A permutation group branded with the lifetime `'id`.
+///
+/// `PermGroup<'id>`, `Permutation<'id>`, and `StabilizerChain<'id>` are
+/// `Send + Sync`: the brand is a zero-sized `Id<'id>`, and the only state
+/// the group caches is the inverses of its generators, computed once in
+/// `new` and never changed, so workers spawned with `std::thread::scope`
+/// can share one group and compose its permutations freely.
 pub struct PermGroup<'id> {
-    base_permutation_length: usize,
-    base_permutations: Vec<Permutation<'id>>,
-    id: Id<'id>,
+    pub(crate) base_permutation_length: usize,
+    pub(crate) base_permutations: Vec<Permutation<'id>>,
+    pub(crate) base_permutation_inverses: Vec<Permutation<'id>>,
+    pub(crate) id: Id<'id>,
 }
 
 impl<'id> PermGroup<'id> {
@@ -17,12 +26,14 @@ impl<'id> PermGroup<'id> {
             validate_permutation(mapping, base_permutation_length)?;
         }
         let id = guard.into();
+        let base_permutations: Vec<_> = base_permutation_mappings
+            .into_iter()
+            .map(|mapping| Permutation(mapping.into_boxed_slice(), id))
+            .collect();
         Ok(Self {
             base_permutation_length,
-            base_permutations: base_permutation_mappings
-                .into_iter()
-                .map(|mapping| Permutation(mapping.into_boxed_slice(), id))
-                .collect(),
+            base_permutation_inverses: base_permutations.iter().map(Permutation::inverse).collect(),
+            base_permutations,
             id,
         })
     }
@@ -30,9 +41,37 @@ impl<'id> PermGroup<'id> {
     pub fn base_permutations(&self) -> &[Permutation<'id>] {
         &self.base_permutations
     }
+
+    pub fn identity(&self) -> Permutation<'id> {
+        Permutation((0..self.base_permutation_length).collect(), self.id)
+    }
+
+    pub fn point(&self, index: usize) -> Option<Point<'id>> {
+        (index < self.base_permutation_length).then_some(Point { index, id: self.id })
+    }
+
+    /// Every point the group acts on, in order.
+    pub fn points(&self) -> impl Iterator<Item = Point<'id>> + use<'id> {
+        let id = self.id;
+        (0..self.base_permutation_length).map(move |index| Point { index, id })
+    }
+}
+
+/// A point that the permutations of a `PermGroup<'id>` act on.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Point<'id> {
+    index: usize,
+    id: Id<'id>,
+}
+
+impl<'id> Point<'id> {
+    pub fn index(self) -> usize {
+        self.index
+    }
 }
 
-pub struct Permutation<'id>(Box<[usize]>, Id<'id>);
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Permutation<'id>(pub(crate) Box<[usize]>, pub(crate) Id<'id>);
 
 impl<'id> Permutation<'id> {
     pub fn from_mapping_and_group(
@@ -52,6 +91,28 @@ impl<'id> Permutation<'id> {
         Ok(permutation)
     }
 
+    /// The image of each point.
+    pub fn mapping(&self) -> &[usize] {
+        &self.0
+    }
+
+    pub fn image(&self, point: Point<'id>) -> Point<'id> {
+        // SAFETY: `self` and `point` have the same lifetime brand, so
+        // `point` is less than the length of `self`, and so is its image.
+        let index = unsafe { *self.0.get_unchecked(point.index) };
+        Point { index, ..point }
+    }
+
+    /// The point that `self` sends to `point`. This searches the mapping,
+    /// so use `image` on the inverse to find many preimages.
+    pub fn preimage(&self, point: Point<'id>) -> Point<'id> {
+        let index = self.0.iter().position(|&image| image == point.index);
+        // SAFETY: As in `image`, `point` is in bounds, and every point is
+        // the image of some point under a permutation.
+        let index = unsafe { index.unwrap_unchecked() };
+        Point { index, ..point }
+    }
+
     pub fn compose_into(&self, b: &Self, result: &mut Self) {
         for i in 0..result.0.len() {
             // SAFETY: `self`, `b`, and `into` have the same
@@ -69,4 +130,180 @@ impl<'id> Permutation<'id> {
         self.compose_into(b, &mut result);
         result
     }
+
+    pub fn inverse_into(&self, result: &mut Self) {
+        for i in 0..result.0.len() {
+            // SAFETY: `self` and `result` have the same lifetime brand.
+            // Therefore, every value of `self` can index `result`.
+            unsafe {
+                *result.0.get_unchecked_mut(*self.0.get_unchecked(i)) = i;
+            }
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut result = Self(vec![0; self.0.len()].into_boxed_slice(), self.1);
+        self.inverse_into(&mut result);
+        result
+    }
+
+    /// The smallest positive power of `self` that is the identity, i.e.
+    /// the least common multiple of its cycle lengths.
+    pub fn order(&self) -> usize {
+        let mut seen = vec![false; self.0.len()];
+        let mut order = 1;
+        for start in 0..self.0.len() {
+            let mut cycle_length = 0;
+            let mut point = start;
+            while !seen[point] {
+                seen[point] = true;
+                point = self.0[point];
+                cycle_length += 1;
+            }
+            if cycle_length > 0 {
+                order = lcm(order, cycle_length);
+            }
+        }
+        order
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+/// Composes every pair in `pairs` into the matching slot of the
+/// preallocated `results`, splitting the batch across scoped threads.
+pub fn par_compose_batch<'id>(
+    pairs: &[(&Permutation<'id>, &Permutation<'id>)],
+    results: &mut [Permutation<'id>],
+) -> Result<(), &'static str> {
+    if pairs.len() != results.len() {
+        return Err("Batch and results must have the same length");
+    }
+    let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = pairs.len().div_ceil(threads).max(1);
+    std::thread::scope(|s| {
+        for (pairs, results) in pairs.chunks(chunk_size).zip(results.chunks_mut(chunk_size)) {
+            s.spawn(move || {
+                for ((a, b), result) in pairs.iter().zip(results) {
+                    a.compose_into(b, result);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Permutation<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserializes a `Permutation<'id>` of `group`.
+///
+/// A brand can't be deserialized on its own, so the group to brand the
+/// permutation with is passed as the seed. The mapping goes through
+/// `Permutation::from_mapping_and_group`, so data that is not a member
+/// of the group is rejected rather than reaching `compose_into`'s
+/// unchecked indexing.
+#[cfg(feature = "serde")]
+pub struct PermutationSeed<'a, 'id>(pub &'a PermGroup<'id>);
+
+#[cfg(feature = "serde")]
+impl<'de, 'id> serde::de::DeserializeSeed<'de> for PermutationSeed<'_, 'id> {
+    type Value = Permutation<'id>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        let mapping = serde::Deserialize::deserialize(deserializer)?;
+        Permutation::from_mapping_and_group(mapping, self.0).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn shareable_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PermGroup<'_>>();
+        assert_send_sync::<Permutation<'_>>();
+        assert_send_sync::<crate::stabilizer_chain::StabilizerChain<'_>>();
+    }
+
+    #[test]
+    fn par_compose_batch_matches_compose() {
+        make_guard!(guard);
+        let group = PermGroup::new(4, vec![vec![1, 2, 3, 0], vec![1, 0, 2, 3]], guard).unwrap();
+        let [r, s] = group.base_permutations() else {
+            unreachable!()
+        };
+        let rs = r.compose(s);
+        let pairs: Vec<_> = (0..100)
+            .map(|i| match i % 3 {
+                0 => (r, s),
+                1 => (s, &rs),
+                _ => (&rs, r),
+            })
+            .collect();
+        let mut results = vec![group.identity(); pairs.len()];
+        par_compose_batch(&pairs, &mut results).unwrap();
+        for ((a, b), result) in pairs.iter().zip(&results) {
+            assert_eq!(a.compose(b).0, result.0);
+        }
+        assert!(par_compose_batch(&pairs, &mut results[1..]).is_err());
+    }
+
+    #[test]
+    fn points_act_without_checks() {
+        make_guard!(guard);
+        let group = PermGroup::new(4, vec![vec![1, 2, 3, 0]], guard).unwrap();
+        let r = &group.base_permutations()[0];
+        assert_eq!(group.point(4), None);
+        let points: Vec<_> = group.points().collect();
+        assert_eq!(points.len(), 4);
+        for &point in &points {
+            assert_eq!(r.image(point).index(), (point.index() + 1) % 4);
+            assert_eq!(r.preimage(r.image(point)), point);
+            assert_eq!(r.inverse().image(point), r.preimage(point));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_seed_revalidates() {
+        use serde::de::DeserializeSeed;
+
+        make_guard!(guard);
+        let group = PermGroup::new(4, vec![vec![1, 0, 2, 3], vec![0, 1, 3, 2]], guard).unwrap();
+        let permutation = group.base_permutations()[0].compose(&group.base_permutations()[1]);
+        let json = serde_json::to_string(&permutation).unwrap();
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        assert_eq!(
+            PermutationSeed(&group)
+                .deserialize(&mut deserializer)
+                .unwrap(),
+            permutation
+        );
+
+        for malformed in ["[1,0,2]", "[1,0,2,4]", "[1,1,2,3]", "[1,2,0,3]"] {
+            let mut deserializer = serde_json::Deserializer::from_str(malformed);
+            assert!(
+                PermutationSeed(&group)
+                    .deserialize(&mut deserializer)
+                    .is_err()
+            );
+        }
+    }
 }
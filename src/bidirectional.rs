@@ -0,0 +1,224 @@
+use crate::mod_5_generativity::Permutation;
+use crate::moves::{Move, MoveSet};
+use crate::stabilizer_chain::Chain;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+/// A meet-in-the-middle search over the moves of a `MoveSet<'id>`: one
+/// breadth-first search expands forward from the identity and another
+/// expands backward from the target until their frontiers meet.
+///
+/// Both frontiers hold permutations of the same branded group, so
+/// elements from either side can be ranked through one stabilizer chain
+/// of the moves and compared without any checks.
+pub struct BidirectionalSearch<'m, 'id> {
+    moves: &'m MoveSet<'id>,
+}
+
+/// The elements one side of the search has reached, by rank.
+struct Frontier {
+    /// The distance of each element and every `(parent, move)` edge that
+    /// reaches it at that distance.
+    reached: HashMap<u128, (usize, Vec<(u128, u16)>)>,
+    layer: Vec<u128>,
+    depth: usize,
+}
+
+impl Frontier {
+    fn new(root: u128) -> Self {
+        Self {
+            reached: HashMap::from([(root, (0, Vec::new()))]),
+            layer: vec![root],
+            depth: 0,
+        }
+    }
+
+    /// Every sequence of moves along shortest paths from the root to
+    /// `rank`, in the order they are walked back from `rank`.
+    fn paths_to_root(&self, rank: u128) -> Vec<Vec<u16>> {
+        let mut paths = Vec::new();
+        let mut stack = vec![(rank, Vec::new())];
+        while let Some((rank, path)) = stack.pop() {
+            let (_, parents) = &self.reached[&rank];
+            if parents.is_empty() {
+                paths.push(path);
+                continue;
+            }
+            for &(parent, index) in parents.iter().rev() {
+                let mut path = path.clone();
+                path.push(index);
+                stack.push((parent, path));
+            }
+        }
+        paths
+    }
+}
+
+impl<'m, 'id> BidirectionalSearch<'m, 'id> {
+    pub fn new(moves: &'m MoveSet<'id>) -> Self {
+        Self { moves }
+    }
+
+    /// Every optimal sequence of at most `max_depth` moves whose product
+    /// is `target`. Returns no sequences if there is none that short.
+    pub fn solve(
+        &self,
+        target: &Permutation<'id>,
+        max_depth: usize,
+    ) -> Result<Vec<Vec<&'m Move<'id>>>, &'static str> {
+        let n = target.0.len();
+        if self.moves.moves().len() > u16::MAX as usize {
+            return Err("Move set is too large");
+        }
+        let chain = Chain::new(
+            n,
+            self.moves.moves().iter().map(|mv| &*mv.permutation().0),
+            (0..n).collect(),
+        );
+        if chain.order().is_none() {
+            return Err("Permutation group is too large to rank");
+        }
+        // A target outside the group the moves generate has no solution.
+        let Some(target_rank) = chain.rank(&target.0) else {
+            return Ok(Vec::new());
+        };
+        let identity = Permutation((0..n).collect(), target.1);
+        let mut forward = Frontier::new(chain.rank_member(&identity.0));
+        let mut backward = Frontier::new(target_rank);
+        if forward.layer == backward.layer {
+            return Ok(vec![Vec::new()]);
+        }
+
+        let mut element = identity.clone();
+        let mut child = identity;
+        while forward.depth + backward.depth < max_depth {
+            // Expand the smaller frontier. Forward elements are extended
+            // by moves on the right, and backward elements by inverse
+            // moves on the right, so a forward element `x` reached by the
+            // moves `a` and a backward element `y` reached by the moves
+            // `b` are equal exactly when `a` followed by `b` reversed
+            // multiplies out to the target.
+            let is_forward = forward.layer.len() <= backward.layer.len();
+            let (frontier, other) = if is_forward {
+                (&mut forward, &backward)
+            } else {
+                (&mut backward, &forward)
+            };
+            let mut next_layer = Vec::new();
+            for &rank in &frontier.layer {
+                chain.unrank(rank, &mut element.0);
+                for (index, mv) in self.moves.moves().iter().enumerate() {
+                    let step = if is_forward {
+                        mv.permutation()
+                    } else {
+                        mv.inverse_permutation()
+                    };
+                    element.compose_into(step, &mut child);
+                    let child_rank = chain.rank_member(&child.0);
+                    match frontier.reached.entry(child_rank) {
+                        Entry::Vacant(entry) => {
+                            entry.insert((frontier.depth + 1, vec![(rank, index as u16)]));
+                            next_layer.push(child_rank);
+                        }
+                        Entry::Occupied(mut entry) => {
+                            let (depth, parents) = entry.get_mut();
+                            if *depth == frontier.depth + 1 {
+                                parents.push((rank, index as u16));
+                            }
+                        }
+                    }
+                }
+            }
+            frontier.depth += 1;
+            frontier.layer = next_layer;
+            if frontier.layer.is_empty() {
+                break;
+            }
+
+            let meetings: Vec<_> = frontier
+                .layer
+                .iter()
+                .filter_map(|rank| Some((*rank, other.reached.get(rank)?.0)))
+                .collect();
+            let Some(shortest) = meetings.iter().map(|&(_, depth)| depth).min() else {
+                continue;
+            };
+            let moves = self.moves.moves();
+            let mut solutions = Vec::new();
+            for (rank, _) in meetings.into_iter().filter(|&(_, depth)| depth == shortest) {
+                for mut forward_path in forward.paths_to_root(rank) {
+                    forward_path.reverse();
+                    for backward_path in backward.paths_to_root(rank) {
+                        solutions.push(
+                            forward_path
+                                .iter()
+                                .chain(&backward_path)
+                                .map(|&index| &moves[index as usize])
+                                .collect(),
+                        );
+                    }
+                }
+            }
+            return Ok(solutions);
+        }
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ida_star::IdaStar;
+    use crate::mod_5_generativity::PermGroup;
+    use crate::puzzle::{CUBE_3X3X3, Puzzle};
+    use crate::word::Word;
+    use generativity::make_guard;
+
+    #[test]
+    fn finds_every_optimal_solution() {
+        make_guard!(guard);
+        let group = PermGroup::new(
+            6,
+            vec![vec![1, 2, 3, 0, 4, 5], vec![0, 1, 5, 3, 2, 4]],
+            guard,
+        )
+        .unwrap();
+        let moves = MoveSet::new(&group, group.letters());
+        let [a, b] = group.base_permutations() else {
+            unreachable!()
+        };
+        let target = a.compose(b).compose(&a.compose(a)).compose(&b.inverse());
+
+        let solutions = BidirectionalSearch::new(&moves).solve(&target, 6).unwrap();
+        let ida_solutions = IdaStar::new(&moves, |_: &Permutation| 0).solve(&target, 6);
+        assert!(solutions.len() >= ida_solutions.len());
+        for solution in &solutions {
+            assert_eq!(solution.len(), ida_solutions[0].len());
+            let word: Word = solution
+                .iter()
+                .flat_map(|mv| mv.word().letters().to_vec())
+                .collect();
+            assert_eq!(word.evaluate(&group), target);
+        }
+    }
+
+    #[test]
+    fn solves_on_the_cube() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        let moves = MoveSet::new(cube.group(), cube.group().letters());
+        let target = cube.parse_permutation("R U R' U'").unwrap();
+        let solutions = BidirectionalSearch::new(&moves).solve(&target, 5).unwrap();
+        assert_eq!(solutions.len(), 1);
+        let product = solutions[0]
+            .iter()
+            .fold(cube.group().identity(), |product, mv| {
+                product.compose(mv.permutation())
+            });
+        assert_eq!(product, target);
+
+        let u_moves = MoveSet::new(cube.group(), [cube.letter("U").unwrap()]);
+        let search = BidirectionalSearch::new(&u_moves);
+        assert!(search.solve(&target, 5).unwrap().is_empty());
+    }
+}
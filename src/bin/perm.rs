@@ -0,0 +1,266 @@
+//! `perm`: permutation group queries from the command line.
+//!
+//! Run `perm --help` for usage.
+
+use generativity::make_guard;
+use generativity_pattern_rs::bidirectional::BidirectionalSearch;
+use generativity_pattern_rs::gap::mapping_from_gap_str;
+use generativity_pattern_rs::mod_5_generativity::{PermGroup, Permutation};
+use generativity_pattern_rs::moves::MoveSet;
+use std::io::{self, BufRead};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: perm [--json] <command> [permutation]...
+
+Commands:
+  compose P Q...        The product P.compose(Q)..., sending i to P[Q[i]]
+  inverse P             The inverse of P
+  order P               The smallest n > 0 such that P^n is the identity
+  cycles P              P in GAP cycle notation
+  member P G...         Whether P is in the group generated by G...
+  group-order G...      The order of the group generated by G...
+  solve P G...          A shortest product of G... and their inverses that
+                        equals P, with generators named g0, g1, ...
+
+A permutation is either a zero-based mapping such as `1,2,0` or `[1 2 0]`,
+or one-based GAP cycle notation such as `(1,2,3)`. Permutations are padded
+with fixed points to the length of the longest one. Without permutation
+arguments, they are read from stdin, one per line.
+
+Options:
+  --json                Print the result as JSON
+  --max-depth N         The longest solution `solve` looks for (default 12)
+  -h, --help            Print this help";
+
+const DEFAULT_MAX_DEPTH: usize = 12;
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("perm: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<String, String> {
+    let mut json = false;
+    let mut max_depth = DEFAULT_MAX_DEPTH;
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(USAGE.to_owned()),
+            "--json" => json = true,
+            "--max-depth" => {
+                max_depth = args
+                    .next()
+                    .and_then(|depth| depth.parse().ok())
+                    .ok_or("--max-depth expects a number")?;
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let Some(command) = positional.first().cloned() else {
+        return Err(format!("missing command\n\n{USAGE}"));
+    };
+    let mut inputs = positional.split_off(1);
+    if inputs.is_empty() {
+        for line in io::stdin().lock().lines() {
+            let line = line.map_err(|error| error.to_string())?;
+            if !line.trim().is_empty() {
+                inputs.push(line);
+            }
+        }
+    }
+    let mut mappings = inputs
+        .iter()
+        .map(|input| parse_mapping(input))
+        .collect::<Result<Vec<_>, _>>()?;
+    let length = mappings.iter().map(Vec::len).max().unwrap_or(0);
+    for mapping in &mut mappings {
+        mapping.extend(mapping.len()..length);
+    }
+
+    make_guard!(guard);
+    let output = match command.as_str() {
+        "compose" | "inverse" | "order" | "cycles" => {
+            if command == "compose" && mappings.is_empty() {
+                return Err("compose expects at least one permutation".to_owned());
+            }
+            if command != "compose" && mappings.len() != 1 {
+                return Err(format!("{command} expects one permutation"));
+            }
+            let group = PermGroup::new(length, mappings, guard)?;
+            let [first, rest @ ..] = group.base_permutations() else {
+                unreachable!()
+            };
+            match command.as_str() {
+                "compose" => format_mapping(
+                    &rest
+                        .iter()
+                        .fold(first.clone(), |product, p| product.compose(p)),
+                    json,
+                ),
+                "inverse" => format_mapping(&first.inverse(), json),
+                "order" => first.order().to_string(),
+                _ => format_string(&first.to_gap_string(), json),
+            }
+        }
+        "member" => {
+            if mappings.is_empty() {
+                return Err("member expects a permutation and its generators".to_owned());
+            }
+            let target = mappings.remove(0);
+            let group = PermGroup::new(length, mappings, guard)?;
+            group.stabilizer_chain().contains(&target).to_string()
+        }
+        "group-order" => {
+            let group = PermGroup::new(length, mappings, guard)?;
+            group
+                .order()
+                .ok_or("group order does not fit in 128 bits")?
+                .to_string()
+        }
+        "solve" => {
+            if mappings.len() < 2 {
+                return Err("solve expects a permutation and its generators".to_owned());
+            }
+            let target = mappings.remove(0);
+            let group = PermGroup::new(length, mappings, guard)?;
+            let target = group
+                .stabilizer_chain()
+                .permutation(target)
+                .map_err(|_| "the permutation is not in the group the generators generate")?;
+            let moves = MoveSet::new(&group, group.letters());
+            let solutions = BidirectionalSearch::new(&moves).solve(&target, max_depth)?;
+            let solution = solutions
+                .first()
+                .ok_or_else(|| format!("no solution within {max_depth} moves"))?;
+            let letters: Vec<_> = solution
+                .iter()
+                .flat_map(|mv| mv.word().letters().to_vec())
+                .map(|letter| {
+                    let prime = if letter.is_inverse() { "'" } else { "" };
+                    format!("g{}{prime}", letter.generator())
+                })
+                .collect();
+            if json {
+                let letters: Vec<_> = letters
+                    .iter()
+                    .map(|letter| format!("\"{letter}\""))
+                    .collect();
+                format!("[{}]", letters.join(","))
+            } else {
+                letters.join(" ")
+            }
+        }
+        _ => return Err(format!("unknown command `{command}`\n\n{USAGE}")),
+    };
+    Ok(output)
+}
+
+/// Reads a zero-based mapping or one-based GAP cycle notation.
+fn parse_mapping(input: &str) -> Result<Vec<usize>, String> {
+    let input = input.trim();
+    let mapping = if input.starts_with('(') {
+        mapping_from_gap_str(input)?
+    } else {
+        input
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("`{input}` is not a mapping or cycle notation"))?
+    };
+    let mut seen = vec![false; mapping.len()];
+    for &image in &mapping {
+        if !seen.get(image).is_some_and(|seen| !seen) {
+            return Err(format!("`{input}` is not a permutation"));
+        }
+        seen[image] = true;
+    }
+    Ok(mapping)
+}
+
+fn format_mapping(permutation: &Permutation, json: bool) -> String {
+    let values: Vec<_> = permutation.mapping().iter().map(usize::to_string).collect();
+    if json {
+        format!("[{}]", values.join(","))
+    } else {
+        values.join(" ")
+    }
+}
+
+fn format_string(value: &str, json: bool) -> String {
+    if json {
+        format!("\"{value}\"")
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity_pattern_rs::puzzle::{CUBE_3X3X3, Puzzle};
+
+    fn perm(args: &[&str]) -> Result<String, String> {
+        run(args.iter().map(|&arg| arg.to_owned()).collect())
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(perm(&["compose", "1,2,0", "(1,2)"]).unwrap(), "2 1 0");
+        assert_eq!(perm(&["--json", "inverse", "[1 2 0]"]).unwrap(), "[2,0,1]");
+        assert_eq!(perm(&["order", "(1,2)(3,4,5)"]).unwrap(), "6");
+        assert_eq!(
+            perm(&["--json", "cycles", "1,0,3,2"]).unwrap(),
+            "\"(1,2)(3,4)\""
+        );
+        assert_eq!(
+            perm(&["member", "(1,3)", "(1,2)", "(2,3)"]).unwrap(),
+            "true"
+        );
+        assert_eq!(perm(&["member", "(1,2)", "(1,2,3)"]).unwrap(), "false");
+        assert_eq!(perm(&["group-order", "(1,2,3,4)", "(1,2)"]).unwrap(), "24");
+        assert_eq!(perm(&["solve", "(1,3,2)", "(1,2,3)"]).unwrap(), "g0'");
+        assert_eq!(
+            perm(&["solve", "(1,2)", "(1,2,3)"]).unwrap_err(),
+            "the permutation is not in the group the generators generate"
+        );
+        assert!(perm(&["order", "1,1"]).is_err());
+        assert!(perm(&["rotate", "1,0"]).is_err());
+    }
+
+    #[test]
+    fn identity_and_trivial_generators() {
+        assert_eq!(perm(&["order", "()"]).unwrap(), "1");
+        assert_eq!(perm(&["cycles", "0,1"]).unwrap(), "()");
+        assert_eq!(perm(&["member", "1,0", "0,1"]).unwrap(), "false");
+        assert_eq!(perm(&["member", "()", "()"]).unwrap(), "true");
+        assert_eq!(perm(&["member", "0,1", "1,0"]).unwrap(), "true");
+        assert_eq!(perm(&["group-order", "0,1"]).unwrap(), "1");
+        assert!(perm(&["solve", "1,0", "0,1"]).is_err());
+        assert_eq!(perm(&["solve", "0,1", "1,0"]).unwrap(), "");
+        assert_eq!(perm(&["solve", "()", "()"]).unwrap(), "");
+    }
+
+    #[test]
+    fn solve_on_large_point_sets() {
+        assert_eq!(perm(&["solve", "(1,2)", "(1,2)", "(35,36)"]).unwrap(), "g0");
+
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        let cycles = |moves| cube.parse_permutation(moves).unwrap().to_gap_string();
+        let (target, r, u) = (cycles("R U'"), cycles("R"), cycles("U"));
+        assert_eq!(perm(&["solve", &target, &r, &u]).unwrap(), "g0 g1'");
+    }
+}
@@ -0,0 +1,240 @@
+use crate::branded_vec::{BrandedIndex, BrandedVec};
+use generativity::{Guard, Id};
+
+/// An arena that only allocates, handing out `BrandedIndex<'id>` handles
+/// that are used without any checks.
+///
+/// To free values as well, convert it with `into_generational`.
+pub struct BrandedArena<'id, T> {
+    values: BrandedVec<'id, T>,
+}
+
+impl<'id, T> BrandedArena<'id, T> {
+    pub fn new(guard: Guard<'id>) -> Self {
+        Self {
+            values: BrandedVec::new(Vec::new(), guard),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize, guard: Guard<'id>) -> Self {
+        Self {
+            values: BrandedVec::new(Vec::with_capacity(capacity), guard),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn alloc(&mut self, value: T) -> BrandedIndex<'id> {
+        self.values.push(value)
+    }
+
+    pub fn get(&self, index: BrandedIndex<'id>) -> &T {
+        self.values.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: BrandedIndex<'id>) -> &mut T {
+        self.values.get_mut(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BrandedIndex<'id>, &T)> {
+        self.values.iter()
+    }
+
+    /// Switches to an arena that can also remove values. Handles from
+    /// this arena carry over with `GenerationalArena::handle`.
+    pub fn into_generational(self) -> GenerationalArena<'id, T> {
+        let id = self.values.id();
+        let slots: Vec<_> = self
+            .values
+            .into_inner()
+            .into_iter()
+            .map(|value| Slot {
+                generation: 0,
+                value: Some(value),
+            })
+            .collect();
+        GenerationalArena {
+            len: slots.len(),
+            slots,
+            free: Vec::new(),
+            id,
+        }
+    }
+}
+
+/// An arena that can remove values as well as allocate them.
+///
+/// The brand still keeps handles in bounds, but a slot can be reused
+/// after its value is removed, so every slot counts how many times it has
+/// been freed. A handle remembers the count it was made with, and using
+/// it after its value was removed returns `None`.
+pub struct GenerationalArena<'id, T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    len: usize,
+    id: Id<'id>,
+}
+
+/// A handle to a value in a `GenerationalArena<'id, T>`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GenerationalIndex<'id> {
+    index: usize,
+    generation: u64,
+    id: Id<'id>,
+}
+
+struct Slot<T> {
+    generation: u64,
+    value: Option<T>,
+}
+
+impl<'id> GenerationalIndex<'id> {
+    pub fn index(self) -> usize {
+        self.index
+    }
+
+    pub fn generation(self) -> u64 {
+        self.generation
+    }
+}
+
+impl<'id, T> GenerationalArena<'id, T> {
+    pub fn new(guard: Guard<'id>) -> Self {
+        BrandedArena::new(guard).into_generational()
+    }
+
+    /// The number of values in the arena.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> GenerationalIndex<'id> {
+        self.len += 1;
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].value = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                self.slots.len() - 1
+            }
+        };
+        GenerationalIndex {
+            index,
+            generation: self.slots[index].generation,
+            id: self.id,
+        }
+    }
+
+    /// Removes and returns the value of `index`, or `None` if it was
+    /// already removed.
+    pub fn remove(&mut self, index: GenerationalIndex<'id>) -> Option<T> {
+        let slot = self.slot_mut(index);
+        if slot.generation != index.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn contains(&self, index: GenerationalIndex<'id>) -> bool {
+        self.get(index).is_some()
+    }
+
+    pub fn get(&self, index: GenerationalIndex<'id>) -> Option<&T> {
+        let slot = self.slot(index);
+        if slot.generation != index.generation {
+            return None;
+        }
+        slot.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: GenerationalIndex<'id>) -> Option<&mut T> {
+        let slot = self.slot_mut(index);
+        if slot.generation != index.generation {
+            return None;
+        }
+        slot.value.as_mut()
+    }
+
+    /// The handle of a value allocated before `into_generational`. It is
+    /// stale if that value has since been removed.
+    pub fn handle(&self, index: BrandedIndex<'id>) -> GenerationalIndex<'id> {
+        GenerationalIndex {
+            index: index.index(),
+            generation: 0,
+            id: self.id,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GenerationalIndex<'id>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = GenerationalIndex {
+                index,
+                generation: slot.generation,
+                id: self.id,
+            };
+            Some((handle, slot.value.as_ref()?))
+        })
+    }
+
+    fn slot(&self, index: GenerationalIndex<'id>) -> &Slot<T> {
+        // SAFETY: `index` has the same brand as `self`, so it was made by
+        // `insert`, `iter`, or `handle` from an index of this arena, and
+        // slots are never dropped.
+        unsafe { self.slots.get_unchecked(index.index) }
+    }
+
+    fn slot_mut(&mut self, index: GenerationalIndex<'id>) -> &mut Slot<T> {
+        // SAFETY: As in `slot`.
+        unsafe { self.slots.get_unchecked_mut(index.index) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn stale_handles_are_rejected() {
+        make_guard!(guard);
+        let mut arena = BrandedArena::new(guard);
+        let a = arena.alloc("a");
+        let b = arena.alloc("b");
+        *arena.get_mut(b) = "B";
+
+        let mut arena = arena.into_generational();
+        let [a, b] = [a, b].map(|index| arena.handle(index));
+        assert_eq!(arena.get(b), Some(&"B"));
+        assert_eq!(arena.remove(a), Some("a"));
+        assert_eq!(arena.remove(a), None);
+
+        let c = arena.insert("c");
+        assert_eq!(c.index(), a.index());
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.get(c), Some(&"c"));
+        assert_eq!(arena.len(), 2);
+        assert_eq!(
+            arena.iter().map(|(_, &v)| v).collect::<Vec<_>>(),
+            ["c", "B"]
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use crate::branded_vec::{BrandedIndex, BrandedVec};
+use generativity::{Guard, Id};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// A directed graph whose node and edge handles carry its brand, so that
+/// looking up a node, an edge, or a node's adjacency never checks bounds,
+/// and handles from another graph don't compile.
+///
+/// Like `BrandedVec`, the graph only grows: there is no way to remove a
+/// node or edge, since that would leave handles dangling.
+pub struct BrandedGraph<'id, N, E> {
+    nodes: BrandedVec<'id, Node<'id, N>>,
+    edges: Vec<Edge<'id, E>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct NodeId<'id>(BrandedIndex<'id>);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EdgeId<'id> {
+    index: usize,
+    id: Id<'id>,
+}
+
+struct Node<'id, N> {
+    weight: N,
+    outgoing: Vec<EdgeId<'id>>,
+}
+
+struct Edge<'id, E> {
+    source: NodeId<'id>,
+    target: NodeId<'id>,
+    weight: E,
+}
+
+impl<'id> NodeId<'id> {
+    pub fn index(self) -> usize {
+        self.0.index()
+    }
+}
+
+impl<'id> EdgeId<'id> {
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+impl<'id, N, E> BrandedGraph<'id, N, E> {
+    pub fn new(guard: Guard<'id>) -> Self {
+        Self {
+            nodes: BrandedVec::new(Vec::new(), guard),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId<'id> {
+        NodeId(self.nodes.push(Node {
+            weight,
+            outgoing: Vec::new(),
+        }))
+    }
+
+    pub fn add_edge(&mut self, source: NodeId<'id>, target: NodeId<'id>, weight: E) -> EdgeId<'id> {
+        let edge = EdgeId {
+            index: self.edges.len(),
+            id: self.nodes.id(),
+        };
+        self.edges.push(Edge {
+            source,
+            target,
+            weight,
+        });
+        self.nodes.get_mut(source.0).outgoing.push(edge);
+        edge
+    }
+
+    pub fn node(&self, node: NodeId<'id>) -> &N {
+        &self.nodes.get(node.0).weight
+    }
+
+    pub fn node_mut(&mut self, node: NodeId<'id>) -> &mut N {
+        &mut self.nodes.get_mut(node.0).weight
+    }
+
+    pub fn edge(&self, edge: EdgeId<'id>) -> &E {
+        &self.edge_data(edge).weight
+    }
+
+    pub fn edge_mut(&mut self, edge: EdgeId<'id>) -> &mut E {
+        // SAFETY: `edge` has the same brand as `self`, so it was returned
+        // by `add_edge`, and edges are never removed.
+        unsafe { &mut self.edges.get_unchecked_mut(edge.index).weight }
+    }
+
+    /// The source and target of `edge`.
+    pub fn endpoints(&self, edge: EdgeId<'id>) -> (NodeId<'id>, NodeId<'id>) {
+        let edge = self.edge_data(edge);
+        (edge.source, edge.target)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId<'id>> + use<'id, N, E> {
+        self.nodes.indices().map(NodeId)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = EdgeId<'id>> + use<'id, N, E> {
+        let id = self.nodes.id();
+        (0..self.edges.len()).map(move |index| EdgeId { index, id })
+    }
+
+    /// The edges leaving `node`, in the order they were added.
+    pub fn outgoing(&self, node: NodeId<'id>) -> &[EdgeId<'id>] {
+        &self.nodes.get(node.0).outgoing
+    }
+
+    pub fn neighbors(&self, node: NodeId<'id>) -> impl Iterator<Item = NodeId<'id>> {
+        self.outgoing(node)
+            .iter()
+            .map(|&edge| self.edge_data(edge).target)
+    }
+
+    /// The nodes reachable from `start`, in breadth-first order.
+    pub fn bfs(&self, start: NodeId<'id>) -> Vec<NodeId<'id>> {
+        let mut visited = vec![false; self.node_count()];
+        visited[start.index()] = true;
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for neighbor in self.neighbors(node) {
+                if !std::mem::replace(&mut visited[neighbor.index()], true) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        order
+    }
+
+    /// The nodes reachable from `start`, in depth-first preorder.
+    pub fn dfs(&self, start: NodeId<'id>) -> Vec<NodeId<'id>> {
+        let mut visited = vec![false; self.node_count()];
+        let mut order = Vec::new();
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            if std::mem::replace(&mut visited[node.index()], true) {
+                continue;
+            }
+            order.push(node);
+            // Push in reverse so that neighbors are visited in the order
+            // their edges were added.
+            let unvisited: Vec<_> = self
+                .neighbors(node)
+                .filter(|neighbor| !visited[neighbor.index()])
+                .collect();
+            stack.extend(unvisited.into_iter().rev());
+        }
+        order
+    }
+
+    /// Every node, ordered so that each edge goes from an earlier node to
+    /// a later one. Fails if the graph has a cycle.
+    pub fn topological_sort(&self) -> Result<Vec<NodeId<'id>>, &'static str> {
+        let mut in_degrees = vec![0usize; self.node_count()];
+        for edge in &self.edges {
+            in_degrees[edge.target.index()] += 1;
+        }
+        let mut ready: VecDeque<_> = self
+            .nodes()
+            .filter(|node| in_degrees[node.index()] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.node_count());
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for neighbor in self.neighbors(node) {
+                in_degrees[neighbor.index()] -= 1;
+                if in_degrees[neighbor.index()] == 0 {
+                    ready.push_back(neighbor);
+                }
+            }
+        }
+        if order.len() != self.node_count() {
+            return Err("Graph contains a cycle");
+        }
+        Ok(order)
+    }
+
+    /// The shortest paths from `start` to every node, where each edge is
+    /// as long as `weight` says.
+    pub fn dijkstra(&self, start: NodeId<'id>, weight: impl Fn(&E) -> u64) -> ShortestPaths<'id> {
+        let mut paths = ShortestPaths {
+            distances: vec![None; self.node_count()],
+            predecessors: vec![None; self.node_count()],
+        };
+        paths.distances[start.index()] = Some(0);
+        let mut queue = BinaryHeap::from([Reverse((0, start))]);
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            if paths.distances[node.index()] != Some(distance) {
+                continue;
+            }
+            for &edge in self.outgoing(node) {
+                let data = self.edge_data(edge);
+                let target = data.target.index();
+                let candidate = distance.saturating_add(weight(&data.weight));
+                if paths.distances[target].is_none_or(|current| candidate < current) {
+                    paths.distances[target] = Some(candidate);
+                    paths.predecessors[target] = Some((node, edge));
+                    queue.push(Reverse((candidate, data.target)));
+                }
+            }
+        }
+        paths
+    }
+
+    fn edge_data(&self, edge: EdgeId<'id>) -> &Edge<'id, E> {
+        // SAFETY: As in `edge_mut`.
+        unsafe { self.edges.get_unchecked(edge.index) }
+    }
+}
+
+/// The result of `BrandedGraph::dijkstra`.
+pub struct ShortestPaths<'id> {
+    distances: Vec<Option<u64>>,
+    predecessors: Vec<Option<(NodeId<'id>, EdgeId<'id>)>>,
+}
+
+impl<'id> ShortestPaths<'id> {
+    /// The length of the shortest path to `node`, if it is reachable.
+    /// Nodes added to the graph after `dijkstra` ran are not.
+    pub fn distance(&self, node: NodeId<'id>) -> Option<u64> {
+        self.distances.get(node.index()).copied().flatten()
+    }
+
+    /// The edges of a shortest path to `node`, if it is reachable.
+    pub fn path(&self, node: NodeId<'id>) -> Option<Vec<EdgeId<'id>>> {
+        self.distance(node)?;
+        let mut path = Vec::new();
+        let mut node = node;
+        // Every node on the path was reached, so it was in the graph when
+        // `dijkstra` ran.
+        while let Some((previous, edge)) = self.predecessors[node.index()] {
+            path.push(edge);
+            node = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn traversals_and_shortest_paths() {
+        make_guard!(guard);
+        let mut graph = BrandedGraph::new(guard);
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.add_node(name));
+        graph.add_edge(a, b, 4);
+        graph.add_edge(a, c, 1);
+        let cb = graph.add_edge(c, b, 2);
+        let bd = graph.add_edge(b, d, 5);
+
+        assert_eq!(graph.bfs(a), [a, b, c, d]);
+        assert_eq!(graph.dfs(a), [a, b, d, c]);
+        assert_eq!(graph.topological_sort(), Ok(vec![a, c, b, d]));
+
+        let paths = graph.dijkstra(a, |&weight| weight);
+        assert_eq!(paths.distance(d), Some(8));
+        assert_eq!(paths.path(d).unwrap()[1..], [cb, bd]);
+        assert_eq!(paths.distance(a), Some(0));
+        assert_eq!(graph.dijkstra(d, |&weight| weight).distance(a), None);
+
+        let e = graph.add_node("e");
+        graph.add_edge(d, e, 1);
+        assert_eq!(paths.distance(e), None);
+        assert_eq!(paths.path(e), None);
+
+        graph.add_edge(d, a, 0);
+        assert!(graph.topological_sort().is_err());
+    }
+}
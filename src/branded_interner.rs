@@ -0,0 +1,113 @@
+use crate::min_generativity::{Guard, Id};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Stores each distinct string once and hands out a `Symbol<'id>` for
+/// it, such as for the move names and piece labels of a puzzle.
+///
+/// Symbols carry the interner's brand, so resolving one needs neither a
+/// bounds check nor a hash lookup. Each string is allocated once and
+/// shared between the list of strings and the map that finds them.
+pub struct BrandedInterner<'id> {
+    strings: Vec<Arc<str>>,
+    symbols: HashMap<Arc<str>, Symbol<'id>>,
+    id: Id<'id>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Symbol<'id> {
+    index: usize,
+    id: Id<'id>,
+}
+
+impl<'id> Symbol<'id> {
+    /// The number of distinct strings interned before this one.
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+impl<'id> BrandedInterner<'id> {
+    pub fn new(guard: Guard<'id>) -> Self {
+        Self {
+            strings: Vec::new(),
+            symbols: HashMap::new(),
+            id: guard.into(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// The symbol for `string`, interning it if it is new.
+    pub fn intern(&mut self, string: &str) -> Symbol<'id> {
+        if let Some(&symbol) = self.symbols.get(string) {
+            return symbol;
+        }
+        let symbol = Symbol {
+            index: self.strings.len(),
+            id: self.id,
+        };
+        let string: Arc<str> = string.into();
+        self.strings.push(Arc::clone(&string));
+        self.symbols.insert(string, symbol);
+        symbol
+    }
+
+    /// The symbol for `string`, if it has been interned.
+    pub fn get(&self, string: &str) -> Option<Symbol<'id>> {
+        self.symbols.get(string).copied()
+    }
+
+    pub fn resolve(&self, symbol: Symbol<'id>) -> &str {
+        // SAFETY: `symbol` has the same brand as `self`, so it was made by
+        // `intern`, and strings are never removed.
+        unsafe { self.strings.get_unchecked(symbol.index) }
+    }
+
+    /// Every symbol and its string, in the order they were interned.
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol<'id>, &str)> {
+        let id = self.id;
+        self.strings
+            .iter()
+            .enumerate()
+            .map(move |(index, string)| (Symbol { index, id }, &**string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::{CUBE_3X3X3, Puzzle};
+
+    #[test]
+    fn interns_move_names() {
+        generativity::make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        let interner_guard = crate::make_guard!();
+        let mut interner = BrandedInterner::new(interner_guard);
+        let symbols: Vec<_> = cube
+            .move_names()
+            .iter()
+            .map(|name| interner.intern(name))
+            .collect();
+        assert_eq!(interner.len(), 6);
+        assert_eq!(interner.intern("R"), interner.get("R").unwrap());
+        assert_eq!(interner.len(), 6);
+        // The list and the map share one allocation per string.
+        assert_eq!(Arc::strong_count(&interner.strings[0]), 2);
+        assert_eq!(interner.get("X"), None);
+        for (symbol, name) in symbols.iter().zip(cube.move_names()) {
+            assert_eq!(interner.resolve(*symbol), name);
+        }
+        assert_eq!(
+            interner.iter().map(|(_, name)| name).collect::<Vec<_>>(),
+            cube.move_names()
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::branded_vec::{BrandedIndex, BrandedVec};
+use generativity::Guard;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+
+/// A map whose keys, once inserted, are witnessed by a `Key<'id>` that
+/// looks up its value without an `Option`.
+///
+/// Like `BrandedVec`, the map only grows: there is no `remove`, since
+/// that would leave a witness without a value.
+pub struct BrandedMap<'id, K, V> {
+    keys: HashMap<K, BrandedIndex<'id>>,
+    values: BrandedVec<'id, V>,
+}
+
+/// Proof that a key is in a `BrandedMap<'id, K, V>`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Key<'id>(BrandedIndex<'id>);
+
+impl<'id, K: Hash + Eq, V> BrandedMap<'id, K, V> {
+    pub fn new(guard: Guard<'id>) -> Self {
+        Self {
+            keys: HashMap::new(),
+            values: BrandedVec::new(Vec::new(), guard),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Sets the value of `key`, returning its witness and the value it
+    /// replaced, if any.
+    pub fn insert(&mut self, key: K, value: V) -> (Key<'id>, Option<V>) {
+        match self.keys.entry(key) {
+            Entry::Occupied(entry) => {
+                let index = *entry.get();
+                let old = std::mem::replace(self.values.get_mut(index), value);
+                (Key(index), Some(old))
+            }
+            Entry::Vacant(entry) => (Key(*entry.insert(self.values.push(value))), None),
+        }
+    }
+
+    /// The witness of `key`, inserting `default()` as its value if it is
+    /// not in the map yet.
+    pub fn get_or_insert_with(&mut self, key: K, default: impl FnOnce() -> V) -> Key<'id> {
+        let values = &mut self.values;
+        Key(*self
+            .keys
+            .entry(key)
+            .or_insert_with(|| values.push(default())))
+    }
+
+    /// The witness of `key`, if it is in the map.
+    pub fn key<Q>(&self, key: &Q) -> Option<Key<'id>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.keys.get(key).copied().map(Key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.keys.contains_key(key)
+    }
+
+    pub fn get(&self, key: Key<'id>) -> &V {
+        self.values.get(key.0)
+    }
+
+    pub fn get_mut(&mut self, key: Key<'id>) -> &mut V {
+        self.values.get_mut(key.0)
+    }
+
+    /// Every key with its witness and value, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, Key<'id>, &V)> {
+        self.keys
+            .iter()
+            .map(|(key, &index)| (key, Key(index), self.values.get(index)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn witnesses_skip_the_option() {
+        make_guard!(guard);
+        let mut map = BrandedMap::new(guard);
+        let (r, old) = map.insert("R".to_owned(), 1);
+        assert_eq!(old, None);
+        let u = map.get_or_insert_with("U".to_owned(), || 2);
+        assert_eq!(map.get_or_insert_with("U".to_owned(), || 3), u);
+        *map.get_mut(u) += 10;
+        assert_eq!(map.insert("R".to_owned(), 4), (r, Some(1)));
+
+        assert_eq!((map.get(r), map.get(u)), (&4, &12));
+        assert_eq!(map.key("U"), Some(u));
+        assert_eq!(map.key("F"), None);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.iter().map(|(_, _, &v)| v).sum::<i32>(), 16);
+    }
+}
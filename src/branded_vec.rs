@@ -1,20 +1,132 @@
-use generativity::{Guard, Id, make_guard};
+use generativity::{Guard, Id};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
 
 /// The Branded Vector Example from §2 of the paper.
 /// Run from the `./ghostcell-examples` directory, with the command
 /// `cargo run --example branded_vec`.
-
-struct BrandedVec<'id, T> {
+///
+/// A vector whose indices carry its brand, so that a `BrandedIndex<'id>`
+/// is known to be in bounds.
+///
+/// There is no `pop`, `truncate`, or `remove`, since shrinking the vector
+/// would leave indices pointing past its end. For the same reason it is
+/// not `Clone`: a clone would share the brand, so an index pushed onto
+/// the original could be used on the shorter clone. Use `clone_branded`
+/// to copy it under a new brand instead.
+pub struct BrandedVec<'id, T> {
     inner: Vec<T>,
     id: Id<'id>,
 }
 
-#[derive(Clone, Copy)]
-struct BrandedIndex<'id> {
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BrandedIndex<'id> {
     idx: usize,
     id: Id<'id>,
 }
 
+impl<'id> BrandedIndex<'id> {
+    pub fn index(self) -> usize {
+        self.idx
+    }
+}
+
+/// A range of indices that is known to be in bounds of the
+/// `BrandedVec<'id, T>` it came from, so that splitting it and indexing
+/// with it need no bounds checks.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BrandedRange<'id> {
+    start: usize,
+    end: usize,
+    id: Id<'id>,
+}
+
+impl<'id> BrandedRange<'id> {
+    pub fn start(self) -> usize {
+        self.start
+    }
+
+    pub fn end(self) -> usize {
+        self.end
+    }
+
+    pub fn len(self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(self, index: BrandedIndex<'id>) -> bool {
+        (self.start..self.end).contains(&index.idx)
+    }
+
+    pub fn indices(self) -> impl Iterator<Item = BrandedIndex<'id>> {
+        (self.start..self.end).map(move |idx| BrandedIndex { idx, id: self.id })
+    }
+
+    pub fn first(self) -> Option<BrandedIndex<'id>> {
+        (!self.is_empty()).then_some(BrandedIndex {
+            idx: self.start,
+            id: self.id,
+        })
+    }
+
+    pub fn last(self) -> Option<BrandedIndex<'id>> {
+        (!self.is_empty()).then_some(BrandedIndex {
+            idx: self.end - 1,
+            id: self.id,
+        })
+    }
+
+    /// Splits into `[start, start + mid)` and `[start + mid, end)`, or
+    /// returns `None` if `mid` is past the end of the range.
+    pub fn split_at(self, mid: usize) -> Option<(Self, Self)> {
+        (mid <= self.len()).then_some((
+            Self {
+                end: self.start + mid,
+                ..self
+            },
+            Self {
+                start: self.start + mid,
+                ..self
+            },
+        ))
+    }
+
+    /// Splits into the range before `index` and the range after it, or
+    /// returns `None` if `index` is not in the range.
+    pub fn split_around(self, index: BrandedIndex<'id>) -> Option<(Self, Self)> {
+        self.contains(index).then_some((
+            Self {
+                end: index.idx,
+                ..self
+            },
+            Self {
+                start: index.idx + 1,
+                ..self
+            },
+        ))
+    }
+
+    pub fn midpoint(self) -> Option<BrandedIndex<'id>> {
+        (!self.is_empty()).then_some(BrandedIndex {
+            idx: self.start + self.len() / 2,
+            id: self.id,
+        })
+    }
+
+    /// Splits a nonempty range around its midpoint, the step of a binary
+    /// search.
+    pub fn split_at_midpoint(self) -> Option<(Self, BrandedIndex<'id>, Self)> {
+        let midpoint = self.midpoint()?;
+        let (before, after) = self.split_around(midpoint)?;
+        Some((before, midpoint, after))
+    }
+}
+
 impl<'id, T> BrandedVec<'id, T> {
     pub fn new(inner: Vec<T>, guard: Guard<'id>) -> BrandedVec<'id, T> {
         BrandedVec {
@@ -35,10 +147,13 @@ impl<'id, T> BrandedVec<'id, T> {
     }
 
     pub fn get(&self, index: BrandedIndex<'id>) -> &T {
+        // SAFETY: `index` has the same brand as `self`, and the vector
+        // never shrinks, so it is in bounds.
         unsafe { self.inner.get_unchecked(index.idx) }
     }
 
     pub fn get_mut<'a>(&'a mut self, index: BrandedIndex<'id>) -> &'a mut T {
+        // SAFETY: As in `get`.
         unsafe { self.inner.get_unchecked_mut(index.idx) }
     }
 
@@ -50,11 +165,135 @@ impl<'id, T> BrandedVec<'id, T> {
         self.inner.push(val);
         index
     }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub(crate) fn id(&self) -> Id<'id> {
+        self.id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Every index of the vector as it is now. The iterator does not
+    /// borrow the vector, so it can be modified along the way.
+    pub fn indices(&self) -> impl Iterator<Item = BrandedIndex<'id>> + use<'id, T> {
+        let id = self.id;
+        (0..self.inner.len()).map(move |idx| BrandedIndex { idx, id })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BrandedIndex<'id>, &T)> {
+        self.indices().zip(&self.inner)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (BrandedIndex<'id>, &mut T)> {
+        self.indices().zip(&mut self.inner)
+    }
+
+    pub fn swap(&mut self, a: BrandedIndex<'id>, b: BrandedIndex<'id>) {
+        let pointer = self.inner.as_mut_ptr();
+        // SAFETY: As in `get`, both indices are in bounds. `ptr::swap`
+        // allows them to be equal.
+        unsafe { std::ptr::swap(pointer.add(a.idx), pointer.add(b.idx)) }
+    }
+
+    /// Mutable references to two different elements at once.
+    pub fn get_pair_mut(
+        &mut self,
+        a: BrandedIndex<'id>,
+        b: BrandedIndex<'id>,
+    ) -> Result<(&mut T, &mut T), &'static str> {
+        if a.idx == b.idx {
+            return Err("Indices must be distinct");
+        }
+        let pointer = self.inner.as_mut_ptr();
+        // SAFETY: As in `get`, both indices are in bounds, and they are
+        // distinct, so the references do not alias.
+        unsafe { Ok((&mut *pointer.add(a.idx), &mut *pointer.add(b.idx))) }
+    }
+
+    /// `range` as a `BrandedRange<'id>`, or `None` if it is out of bounds.
+    pub fn range(&self, range: impl RangeBounds<usize>) -> Option<BrandedRange<'id>> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.inner.len(),
+        };
+        (start <= end && end <= self.inner.len()).then_some(BrandedRange {
+            start,
+            end,
+            id: self.id,
+        })
+    }
+
+    pub fn full_range(&self) -> BrandedRange<'id> {
+        BrandedRange {
+            start: 0,
+            end: self.inner.len(),
+            id: self.id,
+        }
+    }
+
+    pub fn subslice(&self, range: BrandedRange<'id>) -> &[T] {
+        // SAFETY: `range` has the same brand as `self`, and the vector
+        // never shrinks, so it is in bounds.
+        unsafe { self.inner.get_unchecked(range.start..range.end) }
+    }
+
+    pub fn subslice_mut(&mut self, range: BrandedRange<'id>) -> &mut [T] {
+        // SAFETY: As in `subslice`.
+        unsafe { self.inner.get_unchecked_mut(range.start..range.end) }
+    }
+
+    /// Like `slice::binary_search_by`, but narrows a `BrandedRange<'id>`
+    /// so that no step checks bounds. On failure, returns the index where
+    /// a matching element could be inserted.
+    pub fn binary_search_by(
+        &self,
+        mut f: impl FnMut(&T) -> Ordering,
+    ) -> Result<BrandedIndex<'id>, usize> {
+        let mut range = self.full_range();
+        while let Some((before, midpoint, after)) = range.split_at_midpoint() {
+            range = match f(self.get(midpoint)) {
+                Ordering::Less => after,
+                Ordering::Greater => before,
+                Ordering::Equal => return Ok(midpoint),
+            };
+        }
+        Err(range.start)
+    }
+
+    /// A copy of this vector under the brand of `guard`.
+    pub fn clone_branded<'new>(&self, guard: Guard<'new>) -> BrandedVec<'new, T>
+    where
+        T: Clone,
+    {
+        BrandedVec::new(self.inner.clone(), guard)
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.inner
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BrandedVec<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.inner).finish()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use generativity::make_guard;
 
     #[test]
     fn branded_vec_example() {
@@ -94,4 +333,47 @@ mod tests {
         // rejected: i1 is not an index of bvec2
         // println!("{:?}", bvec2.get(i1));
     }
+
+    #[test]
+    fn indices_stay_valid() {
+        make_guard!(guard);
+        let mut bvec = BrandedVec::new(vec![3, 1, 2], guard);
+        let indices: Vec<_> = bvec.indices().collect();
+        for index in bvec.indices() {
+            let value = *bvec.get(index);
+            bvec.push(value * 10);
+        }
+        assert_eq!(bvec.len(), 6);
+        bvec.swap(indices[0], indices[2]);
+        let (a, b) = bvec.get_pair_mut(indices[0], indices[1]).unwrap();
+        std::mem::swap(a, b);
+        assert!(bvec.get_pair_mut(indices[1], indices[1]).is_err());
+        for (index, value) in bvec.iter_mut() {
+            *value += index.index();
+        }
+        assert_eq!(format!("{bvec:?}"), "[1, 3, 5, 33, 14, 25]");
+
+        make_guard!(guard);
+        let copy = bvec.clone_branded(guard);
+        assert_eq!(copy.iter().map(|(_, &value)| value).sum::<usize>(), 81);
+    }
+
+    #[test]
+    fn ranges_narrow_without_checks() {
+        make_guard!(guard);
+        let bvec = BrandedVec::new(vec![1, 3, 5, 7, 9], guard);
+        for (index, &value) in bvec.iter() {
+            assert_eq!(bvec.binary_search_by(|probe| probe.cmp(&value)), Ok(index));
+        }
+        assert_eq!(bvec.binary_search_by(|probe| probe.cmp(&4)), Err(2));
+        assert_eq!(bvec.binary_search_by(|probe| probe.cmp(&10)), Err(5));
+
+        let range = bvec.range(1..=3).unwrap();
+        assert_eq!(bvec.subslice(range), [3, 5, 7]);
+        let (left, right) = range.split_at(1).unwrap();
+        assert_eq!(bvec.subslice(left), [3]);
+        assert_eq!(*bvec.get(right.last().unwrap()), 7);
+        assert!(range.split_at(4).is_none());
+        assert!(bvec.range(4..6).is_none());
+    }
 }
@@ -0,0 +1,169 @@
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use crate::moves::{Move, MoveSet};
+use crate::stabilizer_chain::Chain;
+
+/// Marks the identity in the parent table, which has no parent move.
+const ROOT: u8 = u8::MAX;
+
+/// Marks elements not reached yet in the distance table.
+const UNSEEN: u8 = u8::MAX;
+
+/// The most elements `PermGroup::cayley_bfs` will walk. It keeps one
+/// byte per element, or two with the parent table.
+const MAX_ELEMENTS: u128 = 1 << 32;
+
+/// The result of walking the whole Cayley graph of a `PermGroup<'id>`
+/// with `PermGroup::cayley_bfs`.
+pub struct CayleyBfs<'m, 'id> {
+    moves: &'m MoveSet<'id>,
+    chain: Chain,
+    distance_counts: Vec<u64>,
+    parents: Option<Vec<u8>>,
+}
+
+impl<'id> PermGroup<'id> {
+    /// Walks every element reachable from the identity by `moves`, breadth
+    /// first.
+    ///
+    /// Elements are stored by their rank in a stabilizer chain of the
+    /// group the moves generate, which numbers its elements from 0 up to
+    /// its order. Distances then fit in one byte per element, and the
+    /// optional parent table in another.
+    pub fn cayley_bfs<'m>(
+        &self,
+        moves: &'m MoveSet<'id>,
+        keep_parents: bool,
+    ) -> Result<CayleyBfs<'m, 'id>, &'static str> {
+        let n = self.base_permutation_length;
+        if moves.moves().len() >= ROOT as usize {
+            return Err("Move set is too large");
+        }
+        let chain = Chain::new(
+            n,
+            moves.moves().iter().map(|mv| &*mv.permutation().0),
+            (0..n).collect(),
+        );
+        let order = chain
+            .order()
+            .filter(|&order| order <= MAX_ELEMENTS)
+            .ok_or("Permutation group is too large to walk")? as usize;
+        let identity = self.identity();
+        let identity_rank = chain.rank(&identity.0).unwrap() as usize;
+        let mut distances = vec![UNSEEN; order];
+        distances[identity_rank] = 0;
+        let mut parents = keep_parents.then(|| vec![ROOT; order]);
+        let mut distance_counts = vec![1];
+        let mut element = identity.clone();
+        let mut child = identity;
+        for distance in 0.. {
+            if distance == UNSEEN - 1 {
+                return Err("Cayley graph is too deep");
+            }
+            let mut count = 0;
+            for rank in 0..order {
+                if distances[rank] != distance {
+                    continue;
+                }
+                chain.unrank(rank as u128, &mut element.0);
+                for (index, mv) in moves.moves().iter().enumerate() {
+                    element.compose_into(mv.permutation(), &mut child);
+                    // `child` is a product of moves, so it is in the group.
+                    let child_rank = chain.rank_member(&child.0) as usize;
+                    if distances[child_rank] == UNSEEN {
+                        distances[child_rank] = distance + 1;
+                        if let Some(parents) = &mut parents {
+                            parents[child_rank] = index as u8;
+                        }
+                        count += 1;
+                    }
+                }
+            }
+            if count == 0 {
+                break;
+            }
+            distance_counts.push(count);
+        }
+        Ok(CayleyBfs {
+            moves,
+            chain,
+            distance_counts,
+            parents,
+        })
+    }
+}
+
+impl<'m, 'id> CayleyBfs<'m, 'id> {
+    /// How many elements are at each distance from the identity.
+    pub fn distance_counts(&self) -> &[u64] {
+        &self.distance_counts
+    }
+
+    /// The largest distance of any element from the identity.
+    pub fn diameter(&self) -> usize {
+        self.distance_counts.len() - 1
+    }
+
+    /// The number of elements reached, i.e. the order of the group the
+    /// moves generate.
+    pub fn order(&self) -> u128 {
+        self.distance_counts
+            .iter()
+            .map(|&count| count as u128)
+            .sum()
+    }
+
+    /// A shortest sequence of moves whose product is `element`, if the
+    /// walk kept its parent table.
+    pub fn shortest_word(&self, element: &Permutation<'id>) -> Option<Vec<&'m Move<'id>>> {
+        let parents = self.parents.as_ref()?;
+        let mut sequence = Vec::new();
+        let mut element = element.clone();
+        let mut parent = element.clone();
+        loop {
+            let index = parents[self.chain.rank(&element.0)? as usize];
+            if index == ROOT {
+                break;
+            }
+            let mv = &self.moves.moves()[index as usize];
+            element.compose_into(mv.inverse_permutation(), &mut parent);
+            std::mem::swap(&mut element, &mut parent);
+            sequence.push(mv);
+        }
+        sequence.reverse();
+        Some(sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::{CUBE_2X2X2, Puzzle};
+    use generativity::make_guard;
+
+    #[test]
+    fn two_generator_two_by_two_by_two() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_2X2X2, guard).unwrap();
+        let letters = ["R", "U"].map(|name| cube.letter(name).unwrap());
+        let moves = MoveSet::new(cube.group(), letters);
+        let bfs = cube.group().cayley_bfs(&moves, true).unwrap();
+        assert_eq!(
+            bfs.distance_counts(),
+            [
+                1, 6, 18, 53, 148, 400, 910, 1882, 3276, 4628, 6198, 6325, 4352, 941, 22
+            ]
+        );
+        assert_eq!(bfs.diameter(), 14);
+        assert_eq!(bfs.order(), 29_160);
+
+        let target = cube.parse_permutation("R U R' U'").unwrap();
+        let word = bfs.shortest_word(&target).unwrap();
+        assert_eq!(word.len(), 4);
+        let product = word.iter().fold(cube.group().identity(), |product, mv| {
+            product.compose(mv.permutation())
+        });
+        assert_eq!(product, target);
+        let f = cube.parse_permutation("F").unwrap();
+        assert!(bfs.shortest_word(&f).is_none());
+    }
+}
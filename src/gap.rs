@@ -0,0 +1,275 @@
+//! Reading and writing permutations and groups in the syntax of the GAP
+//! computer algebra system, such as `Group([(1,2,3), (1,2)])`.
+//!
+//! GAP writes permutations as products of cycles on the points `1..=n`,
+//! and a product is applied from left to right, so `(1,2)(2,3)` sends
+//! `1` to `3`.
+
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use generativity::Guard;
+
+impl<'id> PermGroup<'id> {
+    /// Reads a group written as `Group((1,2,3), (1,2))` or
+    /// `Group([(1,2,3), (1,2)])`, optionally followed by a `;`.
+    ///
+    /// GAP does not record how many points a group acts on, so the group
+    /// acts on the points up to the largest one any generator mentions.
+    pub fn from_gap_str(s: &str, guard: Guard<'id>) -> Result<Self, &'static str> {
+        let mut cursor = Cursor::new(s);
+        cursor.expect_keyword("Group")?;
+        cursor.expect(b'(')?;
+        let bracketed = cursor.eat(b'[');
+        let mut generators = Vec::new();
+        let closing = if bracketed { b']' } else { b')' };
+        if !cursor.eat(closing) {
+            loop {
+                generators.push(cursor.cycles()?);
+                if !cursor.eat(b',') {
+                    break;
+                }
+            }
+            cursor.expect(closing)?;
+        }
+        if bracketed {
+            cursor.expect(b')')?;
+        }
+        cursor.eat(b';');
+        cursor.expect_end()?;
+
+        let length = generators
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|&point| point + 1)
+            .max()
+            .unwrap_or(0);
+        PermGroup::new(
+            length,
+            generators
+                .iter()
+                .map(|cycles| mapping(cycles, length))
+                .collect::<Result<_, _>>()?,
+            guard,
+        )
+    }
+
+    /// Writes this group so that it can be pasted into a GAP session.
+    pub fn to_gap_string(&self) -> String {
+        if self.base_permutations.is_empty() {
+            return "Group(())".to_owned();
+        }
+        let generators: Vec<_> = self
+            .base_permutations
+            .iter()
+            .map(Permutation::to_gap_string)
+            .collect();
+        format!("Group([{}])", generators.join(", "))
+    }
+}
+
+impl<'id> Permutation<'id> {
+    /// Reads a permutation in cycle notation, such as `(1,2,3)(4,5)` or
+    /// `()`, that is a member of `group`.
+    pub fn from_gap_str(s: &str, group: &PermGroup<'id>) -> Result<Self, &'static str> {
+        let mut cursor = Cursor::new(s);
+        let cycles = cursor.cycles()?;
+        cursor.expect_end()?;
+        Self::from_mapping_and_group(mapping(&cycles, group.base_permutation_length)?, group)
+    }
+
+    /// Writes this permutation as a product of disjoint cycles, with each
+    /// cycle starting from its smallest point.
+    pub fn to_gap_string(&self) -> String {
+        let mut seen = vec![false; self.0.len()];
+        let mut gap = String::new();
+        for start in 0..self.0.len() {
+            if seen[start] || self.0[start] == start {
+                continue;
+            }
+            let mut point = start;
+            let mut separator = '(';
+            while !seen[point] {
+                seen[point] = true;
+                gap.push(separator);
+                gap.push_str(&(point + 1).to_string());
+                separator = ',';
+                point = self.0[point];
+            }
+            gap.push(')');
+        }
+        if gap.is_empty() {
+            gap.push_str("()");
+        }
+        gap
+    }
+}
+
+/// Reads a permutation in cycle notation without a group to check it
+/// against, as a mapping of the points up to the largest one mentioned.
+pub fn mapping_from_gap_str(s: &str) -> Result<Vec<usize>, &'static str> {
+    let mut cursor = Cursor::new(s);
+    let cycles = cursor.cycles()?;
+    cursor.expect_end()?;
+    let length = cycles.iter().flatten().map(|&point| point + 1).max();
+    mapping(&cycles, length.unwrap_or(0))
+}
+
+/// The mapping of a product of `cycles` of zero-based points, applied
+/// from left to right.
+fn mapping(cycles: &[Vec<usize>], length: usize) -> Result<Vec<usize>, &'static str> {
+    let mut mapping: Vec<usize> = (0..length).collect();
+    let mut cycle_mapping = mapping.clone();
+    for cycle in cycles {
+        for (i, &point) in cycle.iter().enumerate() {
+            *cycle_mapping
+                .get_mut(point)
+                .ok_or("Cycle contains a point greater than the length")? =
+                cycle[(i + 1) % cycle.len()];
+        }
+        for image in &mut mapping {
+            *image = cycle_mapping[*image];
+        }
+        for &point in cycle {
+            cycle_mapping[point] = point;
+        }
+    }
+    Ok(mapping)
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            bytes: s.as_bytes(),
+            position: 0,
+        }
+    }
+
+    /// A product of cycles of zero-based points, where `()` is the empty
+    /// product.
+    fn cycles(&mut self) -> Result<Vec<Vec<usize>>, &'static str> {
+        self.expect(b'(')?;
+        if self.eat(b')') {
+            return Ok(Vec::new());
+        }
+        let mut cycles = Vec::new();
+        loop {
+            let mut cycle = Vec::new();
+            loop {
+                let point = self.number()?;
+                if cycle.contains(&point) {
+                    return Err("Cycle contains duplicate points");
+                }
+                cycle.push(point);
+                if !self.eat(b',') {
+                    break;
+                }
+            }
+            self.expect(b')')?;
+            cycles.push(cycle);
+            if !self.eat(b'(') {
+                return Ok(cycles);
+            }
+        }
+    }
+
+    /// A one-based point, returned zero-based.
+    fn number(&mut self) -> Result<usize, &'static str> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(u8::is_ascii_digit)
+        {
+            self.position += 1;
+        }
+        let number: usize = std::str::from_utf8(&self.bytes[start..self.position])
+            .unwrap()
+            .parse()
+            .map_err(|_| "Expected a point")?;
+        number.checked_sub(1).ok_or("Points start at 1")
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.bytes.get(self.position) == Some(&byte);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), &'static str> {
+        if !self.eat(byte) {
+            return Err(match byte {
+                b'(' => "Expected '('",
+                b')' => "Expected ')'",
+                b']' => "Expected ']'",
+                _ => "Unexpected character",
+            });
+        }
+        Ok(())
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), &'static str> {
+        self.skip_whitespace();
+        if !self.bytes[self.position..].starts_with(keyword.as_bytes()) {
+            return Err("Expected a group");
+        }
+        self.position += keyword.len();
+        Ok(())
+    }
+
+    fn expect_end(&mut self) -> Result<(), &'static str> {
+        self.skip_whitespace();
+        if self.position != self.bytes.len() {
+            return Err("Unexpected text after the end");
+        }
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn round_trip() {
+        make_guard!(guard);
+        let group = PermGroup::from_gap_str("Group((1,2,3)(4,5), (1,2));", guard).unwrap();
+        assert_eq!(group.base_permutation_length, 5);
+        assert_eq!(group.to_gap_string(), "Group([(1,2,3)(4,5), (1,2)])");
+
+        make_guard!(guard);
+        let same = PermGroup::from_gap_str(&group.to_gap_string(), guard).unwrap();
+        assert_eq!(same.to_gap_string(), group.to_gap_string());
+
+        let product = Permutation::from_gap_str("(1,2)(2,3)", &group).unwrap();
+        assert_eq!(product.to_gap_string(), "(1,3,2)");
+        assert_eq!(mapping_from_gap_str("(1,2)(2,3)"), Ok(vec![2, 0, 1]));
+        assert_eq!(group.identity().to_gap_string(), "()");
+        assert!(Permutation::from_gap_str("(1,2)(4,5)", &group).is_ok());
+        assert!(Permutation::from_gap_str("(1,4)", &group).is_err());
+        assert!(Permutation::from_gap_str("(1,6)", &group).is_err());
+        assert!(Permutation::from_gap_str("(0,1)", &group).is_err());
+
+        make_guard!(guard);
+        assert!(PermGroup::from_gap_str("Group([(1,2]", guard).is_err());
+    }
+}
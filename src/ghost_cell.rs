@@ -0,0 +1,141 @@
+//! GhostCell, from "GhostCell: Separating Permissions from Data in Rust".
+//!
+//! A `GhostCell<'id, T>` can be shared freely, but its contents can only
+//! be reached through the one `GhostToken<'id>` with the same brand:
+//! reading needs `&token` and writing needs `&mut token`, so the borrow
+//! checker enforces for every cell at once what `RefCell` checks for each
+//! cell at runtime.
+
+use crate::min_generativity::{Guard, Id};
+use std::cell::UnsafeCell;
+
+/// The permission to access every `GhostCell<'id, T>`. There is only one
+/// per brand, since it is made from the brand's `Guard<'id>`.
+pub struct GhostToken<'id> {
+    _id: Id<'id>,
+}
+
+impl<'id> GhostToken<'id> {
+    pub fn new(guard: Guard<'id>) -> Self {
+        Self { _id: guard.into() }
+    }
+}
+
+#[repr(transparent)]
+pub struct GhostCell<'id, T: ?Sized> {
+    _id: Id<'id>,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: Sending the cell sends the value, and the token is still needed
+// to reach it.
+unsafe impl<'id, T: ?Sized + Send> Send for GhostCell<'id, T> {}
+// SAFETY: Sharing the cell lets threads that share the token read the
+// value together, and lets a thread with the token mutably borrowed
+// write it. This is sound exactly when `T` is `Send + Sync`, as for
+// `RwLock<T>`.
+unsafe impl<'id, T: ?Sized + Send + Sync> Sync for GhostCell<'id, T> {}
+
+impl<'id, T> GhostCell<'id, T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            _id: Id::default(),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<'id, T: ?Sized> GhostCell<'id, T> {
+    pub fn borrow<'a>(&'a self, _token: &'a GhostToken<'id>) -> &'a T {
+        // SAFETY: While the token is borrowed shared, nothing can borrow
+        // this cell mutably.
+        unsafe { &*self.value.get() }
+    }
+
+    pub fn borrow_mut<'a>(&'a self, _token: &'a mut GhostToken<'id>) -> &'a mut T {
+        // SAFETY: While the token is borrowed mutably, nothing else can
+        // borrow this or any other cell with the same brand.
+        unsafe { &mut *self.value.get() }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn from_mut(value: &mut T) -> &mut Self {
+        // SAFETY: `GhostCell<'id, T>` is a transparent wrapper around
+        // `UnsafeCell<T>`, which has the same layout as `T`, and the
+        // unique borrow of `value` moves to the cell.
+        unsafe { &mut *(value as *mut T as *mut Self) }
+    }
+}
+
+impl<'id, T: Default> Default for GhostCell<'id, T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::{Rc, Weak};
+
+    /// A node of a doubly-linked list, whose links are shared with its
+    /// neighbors but can still be changed.
+    struct Node<'id> {
+        value: u32,
+        previous: Option<Weak<GhostCell<'id, Node<'id>>>>,
+        next: Option<Rc<GhostCell<'id, Node<'id>>>>,
+    }
+
+    #[test]
+    fn doubly_linked_list() {
+        let guard = crate::make_guard!();
+        let mut token = GhostToken::new(guard);
+        let new_node = |value| {
+            Rc::new(GhostCell::new(Node {
+                value,
+                previous: None,
+                next: None,
+            }))
+        };
+        let head = new_node(1);
+        let mut tail = head.clone();
+        for value in 2..=4 {
+            let node = new_node(value);
+            node.borrow_mut(&mut token).previous = Some(Rc::downgrade(&tail));
+            tail.borrow_mut(&mut token).next = Some(node.clone());
+            tail = node;
+        }
+
+        let mut node = Some(head.clone());
+        let mut forward = Vec::new();
+        while let Some(current) = node {
+            current.borrow_mut(&mut token).value *= 10;
+            forward.push(current.borrow(&token).value);
+            node = current.borrow(&token).next.clone();
+        }
+        assert_eq!(forward, [10, 20, 30, 40]);
+
+        let mut backward = Vec::new();
+        let mut node = Some(tail);
+        while let Some(current) = node {
+            backward.push(current.borrow(&token).value);
+            node = current
+                .borrow(&token)
+                .previous
+                .as_ref()
+                .and_then(Weak::upgrade);
+        }
+        assert_eq!(backward, [40, 30, 20, 10]);
+
+        let mut value = 5;
+        *GhostCell::from_mut(&mut value).borrow_mut(&mut token) += 1;
+        assert_eq!(value, 6);
+    }
+}
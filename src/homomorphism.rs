@@ -0,0 +1,166 @@
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use crate::stabilizer_chain::Chain;
+use generativity::{Guard, Id};
+
+/// A homomorphism from a `PermGroup<'a>` to a `PermGroup<'b>`, given by
+/// the image of each generator.
+///
+/// It is stored as the stabilizer chain of its graph, the group of pairs
+/// `(g, map(g))` acting on the points of both groups, with the points of
+/// the source first.
+pub struct Homomorphism<'a, 'b> {
+    source_length: usize,
+    target_length: usize,
+    images: Vec<Permutation<'b>>,
+    graph: Chain,
+    _source: Id<'a>,
+    target: Id<'b>,
+}
+
+impl<'a, 'b> Homomorphism<'a, 'b> {
+    /// Fails unless `images` has one permutation per generator of
+    /// `source` and every relation between the generators also holds
+    /// between their images.
+    pub fn new(
+        source: &PermGroup<'a>,
+        target: &PermGroup<'b>,
+        images: Vec<Permutation<'b>>,
+    ) -> Result<Self, &'static str> {
+        if images.len() != source.base_permutations.len() {
+            return Err("Expected one image per generator");
+        }
+        let source_length = source.base_permutation_length;
+        let target_length = target.base_permutation_length;
+        let generators: Vec<_> = source
+            .base_permutations
+            .iter()
+            .zip(&images)
+            .map(|(g, h)| pair(g.mapping(), h.mapping()))
+            .collect();
+        let graph = Chain::new(
+            source_length + target_length,
+            generators.iter().map(|generator| &**generator),
+            (0..source_length + target_length).collect(),
+        );
+        // The graph has an element `(1, h)` with `h` not the identity,
+        // and so some `g` has two images, exactly when the base has to go
+        // on past the points of the source.
+        if graph
+            .levels
+            .iter()
+            .any(|level| level.base_point >= source_length)
+        {
+            return Err("Images do not define a homomorphism");
+        }
+        Ok(Self {
+            source_length,
+            target_length,
+            images,
+            graph,
+            _source: source.id,
+            target: target.id,
+        })
+    }
+
+    pub fn map(&self, permutation: &Permutation<'a>) -> Permutation<'b> {
+        let identity: Vec<_> = (0..self.target_length).collect();
+        let element = pair(permutation.mapping(), &identity);
+        let (residue, _) = self.graph.sift(element, 0);
+        // Sifting `(g, 1)` through the graph leaves `(1, map(g)^-1)`.
+        let mut mapping = vec![0; self.target_length].into_boxed_slice();
+        for (point, &image) in residue[self.source_length..].iter().enumerate() {
+            mapping[image - self.source_length] = point;
+        }
+        Permutation(mapping, self.target)
+    }
+
+    /// The elements that map to the identity, as a group of their own.
+    pub fn kernel<'new>(&self, guard: Guard<'new>) -> PermGroup<'new> {
+        // The first level's generators generate the whole graph.
+        let generators: Vec<_> = self
+            .graph
+            .levels
+            .first()
+            .map_or(&[][..], |level| &level.generators)
+            .iter()
+            .map(|generator| &**generator)
+            .collect();
+        let target_points: Vec<_> =
+            (self.source_length..self.source_length + self.target_length).collect();
+        let kernel = Chain::with_base_prefix(
+            self.source_length + self.target_length,
+            generators,
+            &target_points,
+            (0..self.source_length + self.target_length).collect(),
+        );
+        // The kernel is the part of the graph that fixes every point of
+        // the target.
+        let mappings = kernel
+            .levels
+            .get(self.target_length)
+            .map_or(&[][..], |level| &level.generators)
+            .iter()
+            .map(|generator| generator[..self.source_length].to_vec())
+            .collect();
+        PermGroup::new(self.source_length, mappings, guard).unwrap()
+    }
+
+    /// The images of every element, as a group of their own.
+    pub fn image<'new>(&self, guard: Guard<'new>) -> PermGroup<'new> {
+        let mappings = self
+            .images
+            .iter()
+            .map(|image| image.mapping().to_vec())
+            .collect();
+        PermGroup::new(self.target_length, mappings, guard).unwrap()
+    }
+}
+
+/// The permutation acting as `g` on the first points and as `h` on the
+/// rest.
+fn pair(g: &[usize], h: &[usize]) -> Box<[usize]> {
+    g.iter()
+        .copied()
+        .chain(h.iter().map(|&image| image + g.len()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::{CUBE_3X3X3, Puzzle};
+    use generativity::make_guard;
+
+    #[test]
+    fn corners_of_the_cube() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        let corners = cube.orbit("CORNERS").unwrap().point(0, 0).unwrap();
+        let restrict = |p: &[usize]| p[corners..].iter().map(|&i| i - corners).collect();
+        let mappings = cube
+            .group()
+            .base_permutations()
+            .iter()
+            .map(|p| restrict(p.mapping()))
+            .collect();
+        make_guard!(guard);
+        let corner_group = PermGroup::new(24, mappings, guard).unwrap();
+        let images = corner_group.base_permutations().to_vec();
+        let projection = Homomorphism::new(cube.group(), &corner_group, images).unwrap();
+
+        let sune = cube.parse_permutation("R U R' U R U2 R'").unwrap();
+        let image: Vec<usize> = restrict(sune.mapping());
+        assert_eq!(projection.map(&sune).mapping(), image);
+        make_guard!(guard);
+        assert_eq!(projection.image(guard).order(), Some(88_179_840));
+        make_guard!(guard);
+        assert_eq!(projection.kernel(guard).order(), Some(490_497_638_400));
+
+        make_guard!(guard);
+        let cyclic = PermGroup::new(4, vec![vec![1, 2, 3, 0]], guard).unwrap();
+        make_guard!(guard);
+        let symmetric = PermGroup::new(3, vec![vec![1, 2, 0]], guard).unwrap();
+        let images = symmetric.base_permutations().to_vec();
+        assert!(Homomorphism::new(&cyclic, &symmetric, images).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::mod_5_generativity::Permutation;
+use crate::moves::{Move, MoveSet};
+
+/// A lower bound on the number of moves needed to bring `state` back to
+/// the identity. It must never overestimate, or `IdaStar` may return
+/// solutions that are not optimal.
+pub trait Heuristic<'id> {
+    fn estimate(&self, state: &Permutation<'id>) -> usize;
+}
+
+impl<'id, F: Fn(&Permutation<'id>) -> usize> Heuristic<'id> for F {
+    fn estimate(&self, state: &Permutation<'id>) -> usize {
+        self(state)
+    }
+}
+
+/// An iterative-deepening A* search over the moves of a `MoveSet<'id>`.
+pub struct IdaStar<'m, 'id, H> {
+    moves: &'m MoveSet<'id>,
+    heuristic: H,
+}
+
+impl<'m, 'id, H: Heuristic<'id>> IdaStar<'m, 'id, H> {
+    pub fn new(moves: &'m MoveSet<'id>, heuristic: H) -> Self {
+        Self { moves, heuristic }
+    }
+
+    /// Every optimal sequence of at most `max_depth` moves whose product
+    /// is `target`, skipping sequences that `MoveSet::is_redundant`
+    /// rules out. Returns no sequences if there is none that short.
+    pub fn solve(&self, target: &Permutation<'id>, max_depth: usize) -> Vec<Vec<&'m Move<'id>>> {
+        let identity = Permutation((0..target.0.len()).collect(), target.1);
+        // One preallocated state per depth, so expanding a node never
+        // allocates.
+        let mut states = vec![target.clone(); max_depth + 1];
+        let mut path = Vec::with_capacity(max_depth);
+        let mut solutions = Vec::new();
+        let mut bound = self.heuristic.estimate(target);
+        while bound <= max_depth {
+            let next_bound = self.search(&mut states, &mut path, bound, &identity, &mut solutions);
+            if !solutions.is_empty() || next_bound == usize::MAX {
+                break;
+            }
+            bound = next_bound;
+        }
+        solutions
+    }
+
+    /// Searches below `states[0]` and returns the smallest estimate that
+    /// exceeded `bound`.
+    ///
+    /// A node's state is the target multiplied on the left by the
+    /// inverses of the moves on the path to it, so a node is solved once
+    /// its state is the identity.
+    fn search(
+        &self,
+        states: &mut [Permutation<'id>],
+        path: &mut Vec<usize>,
+        bound: usize,
+        identity: &Permutation<'id>,
+        solutions: &mut Vec<Vec<&'m Move<'id>>>,
+    ) -> usize {
+        let (state, children) = states.split_first_mut().unwrap();
+        let estimate = path.len() + self.heuristic.estimate(state);
+        if estimate > bound {
+            return estimate;
+        }
+        if state == identity {
+            let moves = self.moves.moves();
+            solutions.push(path.iter().map(|&index| &moves[index]).collect());
+            return usize::MAX;
+        }
+        if children.is_empty() {
+            return usize::MAX;
+        }
+        let mut next_bound = usize::MAX;
+        for (index, mv) in self.moves.moves().iter().enumerate() {
+            if let Some(&previous) = path.last()
+                && self.moves.is_redundant(previous, index)
+            {
+                continue;
+            }
+            mv.inverse_permutation()
+                .compose_into(state, &mut children[0]);
+            path.push(index);
+            next_bound = next_bound.min(self.search(children, path, bound, identity, solutions));
+            path.pop();
+        }
+        next_bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mod_5_generativity::PermGroup;
+    use crate::word::Word;
+    use generativity::make_guard;
+
+    #[test]
+    fn finds_optimal_solutions() {
+        make_guard!(guard);
+        let group = PermGroup::new(
+            6,
+            vec![vec![1, 2, 3, 0, 4, 5], vec![0, 1, 5, 3, 2, 4]],
+            guard,
+        )
+        .unwrap();
+        let moves = MoveSet::new(&group, group.letters());
+        let [a, b] = group.base_permutations() else {
+            unreachable!()
+        };
+        let target = a.compose(b).compose(&a.compose(a)).compose(&b.inverse());
+
+        let solver = IdaStar::new(&moves, |_: &Permutation| 0);
+        let solutions = solver.solve(&target, 6);
+        let length = solutions[0].len();
+        assert!(length <= 4);
+        assert!(solver.solve(&target, length - 1).is_empty());
+        for solution in &solutions {
+            assert_eq!(solution.len(), length);
+            let word: Word = solution
+                .iter()
+                .flat_map(|mv| mv.word().letters().to_vec())
+                .collect();
+            assert_eq!(word.evaluate(&group), target);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::homomorphism::Homomorphism;
+use crate::mod_5_generativity::{PermGroup, Point};
+use generativity::Guard;
+use std::collections::HashMap;
+
+/// The most elements `PermGroup::induced_domain` will list. The induced
+/// group has a point for each, and the homomorphism onto it stores
+/// stabilizer chain elements that move all of them.
+const MAX_DOMAIN: u128 = 1 << 16;
+
+/// What a group acts on in `PermGroup::induced_action`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Action<'id> {
+    /// The sets of this many points.
+    Sets(usize),
+    /// The tuples of this many distinct points.
+    Tuples(usize),
+    /// The points of the orbit of a point, which is the group restricted
+    /// to that orbit.
+    Orbit(Point<'id>),
+    /// Disjoint sets of points that every element of the group permutes
+    /// among themselves.
+    Blocks(Vec<Vec<Point<'id>>>),
+}
+
+impl<'id> PermGroup<'id> {
+    /// The group as it permutes the sets, tuples, or blocks of `action`,
+    /// along with the homomorphism onto it. Point `i` of the new group is
+    /// element `i` of `induced_domain(action)`.
+    pub fn induced_action<'new>(
+        &self,
+        action: Action<'id>,
+        guard: Guard<'new>,
+    ) -> Result<(PermGroup<'new>, Homomorphism<'id, 'new>), &'static str> {
+        let domain = self.induced_domain(&action)?;
+        let sorted = !matches!(action, Action::Tuples(_));
+        let indices: HashMap<&[usize], usize> = domain
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (&element[..], index))
+            .collect();
+        let mut mappings = Vec::with_capacity(self.base_permutations.len());
+        for generator in &self.base_permutations {
+            let mut mapping = Vec::with_capacity(domain.len());
+            for element in &domain {
+                let mut image: Vec<_> = element.iter().map(|&p| generator.0[p]).collect();
+                if sorted {
+                    image.sort_unstable();
+                }
+                let index = indices
+                    .get(&image[..])
+                    .ok_or("Blocks are not permuted by the group")?;
+                mapping.push(*index);
+            }
+            mappings.push(mapping);
+        }
+        let induced = PermGroup::new(domain.len(), mappings, guard)?;
+        let homomorphism = Homomorphism::new(self, &induced, induced.base_permutations().to_vec())?;
+        Ok((induced, homomorphism))
+    }
+
+    /// The elements that `action` permutes, in order. Sets and blocks are
+    /// listed with their points in increasing order. Fails if there would
+    /// be more than `MAX_DOMAIN` of them.
+    pub fn induced_domain(&self, action: &Action<'id>) -> Result<Vec<Vec<usize>>, &'static str> {
+        let n = self.base_permutation_length;
+        match action {
+            &Action::Sets(k) | &Action::Tuples(k) if k > n => Err("Not enough points"),
+            &Action::Sets(k) | &Action::Tuples(k)
+                if domain_size(n, k, matches!(action, Action::Sets(_)))
+                    .is_none_or(|size| size > MAX_DOMAIN) =>
+            {
+                Err("Induced action is too large")
+            }
+            &Action::Sets(k) => {
+                let mut sets = Vec::new();
+                choose(&mut Vec::with_capacity(k), 0, n, k, &mut sets);
+                Ok(sets)
+            }
+            &Action::Tuples(k) => {
+                let mut tuples = vec![Vec::with_capacity(k)];
+                for _ in 0..k {
+                    let mut longer = Vec::new();
+                    for tuple in &tuples {
+                        for p in (0..n).filter(|p| !tuple.contains(p)) {
+                            let mut next = tuple.clone();
+                            next.push(p);
+                            longer.push(next);
+                        }
+                    }
+                    tuples = longer;
+                }
+                Ok(tuples)
+            }
+            Action::Orbit(point) => {
+                let mut seen = vec![false; n];
+                seen[point.index()] = true;
+                let mut orbit = vec![point.index()];
+                let mut index = 0;
+                while index < orbit.len() {
+                    for generator in &self.base_permutations {
+                        let image = generator.0[orbit[index]];
+                        if !std::mem::replace(&mut seen[image], true) {
+                            orbit.push(image);
+                        }
+                    }
+                    index += 1;
+                }
+                if orbit.len() as u128 > MAX_DOMAIN {
+                    return Err("Induced action is too large");
+                }
+                orbit.sort_unstable();
+                Ok(orbit.into_iter().map(|p| vec![p]).collect())
+            }
+            Action::Blocks(blocks) if blocks.len() as u128 > MAX_DOMAIN => {
+                Err("Induced action is too large")
+            }
+            Action::Blocks(blocks) => {
+                let mut seen = vec![false; n];
+                let mut sorted = Vec::with_capacity(blocks.len());
+                for block in blocks {
+                    if block.is_empty() {
+                        return Err("Blocks must not be empty");
+                    }
+                    for point in block {
+                        if std::mem::replace(&mut seen[point.index()], true) {
+                            return Err("Blocks must be disjoint");
+                        }
+                    }
+                    let mut block: Vec<_> = block.iter().map(|point| point.index()).collect();
+                    block.sort_unstable();
+                    sorted.push(block);
+                }
+                Ok(sorted)
+            }
+        }
+    }
+}
+
+/// The number of `k`-sets, or of `k`-tuples of distinct points, of `n`
+/// points, or `None` if it does not fit in a `u128`.
+fn domain_size(n: usize, k: usize, sets: bool) -> Option<u128> {
+    (0..k).try_fold(1u128, |count, i| {
+        let count = count.checked_mul((n - i) as u128)?;
+        // The product of `i + 1` consecutive integers is divisible by
+        // `(i + 1)!`, so dividing as we go is exact.
+        Some(if sets { count / (i + 1) as u128 } else { count })
+    })
+}
+
+/// Pushes every increasing `k`-tuple of `from..n` that extends `prefix`.
+fn choose(prefix: &mut Vec<usize>, from: usize, n: usize, k: usize, sets: &mut Vec<Vec<usize>>) {
+    if prefix.len() == k {
+        sets.push(prefix.clone());
+        return;
+    }
+    for p in from..=n - (k - prefix.len()) {
+        prefix.push(p);
+        choose(prefix, p + 1, n, k, sets);
+        prefix.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn induced_actions() {
+        make_guard!(guard);
+        // The symmetric group on 4 points, and a copy of it on 4..8.
+        let group = PermGroup::new(
+            8,
+            vec![vec![1, 2, 3, 0, 5, 6, 7, 4], vec![1, 0, 2, 3, 5, 4, 6, 7]],
+            guard,
+        )
+        .unwrap();
+        let cases = [
+            (Action::Sets(2), 28, 24),
+            (Action::Tuples(2), 56, 24),
+            (Action::Orbit(group.point(5).unwrap()), 4, 24),
+        ];
+        for (action, points, order) in cases {
+            make_guard!(guard);
+            let (induced, homomorphism) = group.induced_action(action.clone(), guard).unwrap();
+            assert_eq!(induced.base_permutation_length, points);
+            assert_eq!(induced.order(), Some(order));
+            let generator = &group.base_permutations()[0];
+            assert_eq!(
+                &homomorphism.map(generator),
+                &induced.base_permutations()[0]
+            );
+        }
+
+        let points: Vec<_> = group.points().collect();
+        let pairs = (0..4).map(|p| vec![points[p], points[p + 4]]).collect();
+        make_guard!(guard);
+        let (induced, homomorphism) = group.induced_action(Action::Blocks(pairs), guard).unwrap();
+        assert_eq!(induced.order(), Some(24));
+        make_guard!(guard);
+        assert_eq!(homomorphism.kernel(guard).order(), Some(1));
+
+        let not_blocks = vec![points[0..2].to_vec(), points[2..4].to_vec()];
+        make_guard!(guard);
+        assert!(
+            group
+                .induced_action(Action::Blocks(not_blocks), guard)
+                .is_err()
+        );
+        let overlapping = vec![points[0..2].to_vec(), points[1..3].to_vec()];
+        assert!(group.induced_domain(&Action::Blocks(overlapping)).is_err());
+        assert_eq!(
+            group.induced_domain(&Action::Sets(9)),
+            Err("Not enough points")
+        );
+
+        make_guard!(guard);
+        let large = PermGroup::new(64, vec![(1..64).chain([0]).collect()], guard).unwrap();
+        assert_eq!(
+            large.induced_domain(&Action::Tuples(5)),
+            Err("Induced action is too large")
+        );
+        assert_eq!(
+            large.induced_domain(&Action::Sets(32)),
+            Err("Induced action is too large")
+        );
+        assert_eq!(
+            large.induced_domain(&Action::Sets(3)).unwrap().len(),
+            41_664
+        );
+
+        let n = MAX_DOMAIN as usize + 1;
+        make_guard!(guard);
+        let cycle = PermGroup::new(n, vec![(1..n).chain([0]).collect()], guard).unwrap();
+        let points: Vec<_> = cycle.points().collect();
+        assert_eq!(
+            cycle.induced_domain(&Action::Orbit(points[0])),
+            Err("Induced action is too large")
+        );
+        let singletons = points.iter().map(|&point| vec![point]).collect();
+        assert_eq!(
+            cycle.induced_domain(&Action::Blocks(singletons)),
+            Err("Induced action is too large")
+        );
+    }
+}
@@ -17,8 +17,28 @@ pub mod mod_6_unsound_token;
 // #[path = "7-nonunifiable_proposal.rs"]
 // pub mod mod_7_nonunifiable_proposal;
 
+pub mod bidirectional;
+pub mod branded_arena;
+pub mod branded_graph;
+pub mod branded_interner;
+pub mod branded_map;
 pub mod branded_vec;
+pub mod cayley;
+pub mod gap;
+pub mod ghost_cell;
+pub mod homomorphism;
+pub mod ida_star;
+pub mod induced_action;
 pub mod min_generativity;
+pub mod moves;
+pub mod notation;
+pub mod oriented;
+pub mod pattern_database;
+pub mod perm_arena;
+pub mod puzzle;
+pub mod rewrite;
+pub mod stabilizer_chain;
+pub mod word;
 
 fn validate_permutation(mapping: &[usize], expected_length: usize) -> Result<(), &'static str> {
     if mapping.len() != expected_length {
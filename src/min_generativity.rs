@@ -2,8 +2,30 @@ use std::marker::PhantomData;
 
 pub type Id<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
 
+/// The unique brand of one `make_guard!()`.
+///
+/// Only the macro can make a guard, so code outside this module can't
+/// forge one for a brand it already has:
+///
+/// ```compile_fail
+/// use generativity_pattern_rs::min_generativity::Guard;
+///
+/// let forged: Guard<'static> = Guard(std::marker::PhantomData);
+/// ```
 #[derive(Eq, PartialEq, Debug)]
-pub struct Guard<'id>(pub Id<'id>);
+pub struct Guard<'id>(Id<'id>);
+
+impl<'id> Guard<'id> {
+    /// Used by `make_guard!()`, which is the only way to make a guard.
+    ///
+    /// # Safety
+    ///
+    /// `'id` must be a fresh lifetime that no other guard has.
+    #[doc(hidden)]
+    pub unsafe fn new_unchecked(id: Id<'id>) -> Self {
+        Guard(id)
+    }
+}
 
 impl<'id> From<Guard<'id>> for Id<'id> {
     fn from(guard: Guard<'id>) -> Self {
@@ -32,8 +54,10 @@ macro_rules! make_guard {
     // };
     () => {{
         super let branded_place: $crate::min_generativity::Id = std::marker::PhantomData;
-        super let lifetime_brand = $crate::min_generativity::LifetimeBrand::new(&branded_place);
-        $crate::min_generativity::Guard(branded_place)
+        super let _lifetime_brand = $crate::min_generativity::LifetimeBrand::new(&branded_place);
+        // SAFETY: `branded_place` is borrowed by `_lifetime_brand` until
+        // the end of the enclosing block, so `'id` is unique to it.
+        unsafe { $crate::min_generativity::Guard::new_unchecked(branded_place) }
     }};
 }
 
@@ -66,6 +90,13 @@ mod tests {
         let id: Id<'_> = a.into();
         assert_oibits(&id);
 
+        let b = make_guard!();
+        let token = crate::ghost_cell::GhostToken::new(b);
+        assert_oibits(&token);
+        // A cell is not `RefUnwindSafe`, like the `UnsafeCell` inside it.
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&crate::ghost_cell::GhostCell::<'_, u8>::new(0));
+
         // const compatible (e.g. const_refs_to_cell, const destructor)
         const fn _const_id(_: Id<'_>) {}
         const fn _const_ref_id(_: &'_ Id<'_>) {}
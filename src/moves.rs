@@ -0,0 +1,101 @@
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use crate::word::{Letter, Word};
+
+/// A generator of a `PermGroup<'id>` raised to a power, such as `R2`.
+pub struct Move<'id> {
+    letter: Letter<'id>,
+    power: usize,
+    order: usize,
+    permutation: Permutation<'id>,
+    inverse_permutation: Permutation<'id>,
+}
+
+impl<'id> Move<'id> {
+    pub fn letter(&self) -> Letter<'id> {
+        self.letter
+    }
+
+    pub fn power(&self) -> usize {
+        self.power
+    }
+
+    pub fn permutation(&self) -> &Permutation<'id> {
+        &self.permutation
+    }
+
+    pub fn inverse_permutation(&self) -> &Permutation<'id> {
+        &self.inverse_permutation
+    }
+
+    /// The shortest word for this move, e.g. `R R R` is written `R'`.
+    pub fn word(&self) -> Word<'id> {
+        if self.power <= self.order / 2 {
+            std::iter::repeat_n(self.letter, self.power).collect()
+        } else {
+            std::iter::repeat_n(self.letter.inverse(), self.order - self.power).collect()
+        }
+    }
+}
+
+/// The moves a search may apply: a subset of the generators of a
+/// `PermGroup<'id>`, each with all of its nontrivial powers.
+pub struct MoveSet<'id> {
+    moves: Vec<Move<'id>>,
+    commutes: Vec<Vec<bool>>,
+}
+
+impl<'id> MoveSet<'id> {
+    pub fn new(group: &PermGroup<'id>, letters: impl IntoIterator<Item = Letter<'id>>) -> Self {
+        let mut moves = Vec::new();
+        for letter in letters {
+            let letter = if letter.is_inverse() {
+                letter.inverse()
+            } else {
+                letter
+            };
+            let generator = group.letter_permutation(letter);
+            let order = generator.order();
+            let mut permutation = generator.clone();
+            for power in 1..order {
+                moves.push(Move {
+                    letter,
+                    power,
+                    order,
+                    inverse_permutation: permutation.inverse(),
+                    permutation: permutation.clone(),
+                });
+                permutation = permutation.compose(generator);
+            }
+        }
+        let commutes = moves
+            .iter()
+            .map(|a| {
+                moves
+                    .iter()
+                    .map(|b| {
+                        a.permutation.compose(&b.permutation)
+                            == b.permutation.compose(&a.permutation)
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { moves, commutes }
+    }
+
+    pub fn moves(&self) -> &[Move<'id>] {
+        &self.moves
+    }
+
+    /// Whether the move at index `next` can be skipped right after the
+    /// move at index `previous`: either they turn the same generator
+    /// and would merge into one move, or they commute and the other
+    /// order is the one that gets searched.
+    pub fn is_redundant(&self, previous: usize, next: usize) -> bool {
+        let (previous_generator, next_generator) = (
+            self.moves[previous].letter.generator(),
+            self.moves[next].letter.generator(),
+        );
+        previous_generator == next_generator
+            || (self.commutes[previous][next] && previous_generator > next_generator)
+    }
+}
@@ -0,0 +1,299 @@
+//! Move sequences such as `R U R' U'`, read and written with the move
+//! names of a `Puzzle<'id>`.
+//!
+//! A sequence is a list of whitespace separated items. An item is a move
+//! name, a parenthesized sequence, a conjugate `[A: B]` (`A B A'`), or a
+//! commutator `[A, B]` (`A B A' B'`), and any item may be followed by a
+//! power such as `2` and a `'` to invert it, in either order: `R2'` and
+//! `R'2` are the same. Move names are read as runs of ASCII letters and
+//! underscores, so `RU` is one unknown move.
+
+use crate::mod_5_generativity::Permutation;
+use crate::puzzle::Puzzle;
+use crate::word::Word;
+use std::fmt;
+use std::ops::Range;
+
+/// Why a move sequence failed to parse, and the bytes of the sequence
+/// at fault.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    pub message: &'static str,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<'id> Puzzle<'id> {
+    pub fn parse_word(&self, notation: &str) -> Result<Word<'id>, ParseError> {
+        let mut parser = Parser {
+            puzzle: self,
+            notation: notation.as_bytes(),
+            position: 0,
+            orders: vec![None; self.group().base_permutations().len()],
+        };
+        let word = parser.sequence()?;
+        match parser.peek() {
+            None => Ok(word),
+            Some(b')') => Err(parser.error("Unmatched closing parenthesis", 1)),
+            Some(b']') => Err(parser.error("Unmatched closing bracket", 1)),
+            Some(_) => Err(parser.error("Separator outside of brackets", 1)),
+        }
+    }
+
+    pub fn parse_permutation(&self, notation: &str) -> Result<Permutation<'id>, ParseError> {
+        Ok(self.parse_word(notation)?.evaluate(self.group()))
+    }
+
+    /// Writes `word` in move notation, merging runs of the same move into
+    /// one power, such as `R R R` into `R'`.
+    pub fn to_notation(&self, word: &Word<'id>) -> String {
+        // Each run as its move and its power modulo the move's order.
+        let mut runs: Vec<(usize, usize, usize)> = Vec::new();
+        for &letter in word.letters() {
+            let generator = letter.generator();
+            let order = self.group().base_permutations()[generator].order();
+            let power = if letter.is_inverse() { order - 1 } else { 1 };
+            match runs.last_mut() {
+                Some((last, last_power, _)) if *last == generator => {
+                    *last_power = (*last_power + power) % order;
+                    if *last_power == 0 {
+                        runs.pop();
+                    }
+                }
+                _ if power % order != 0 => runs.push((generator, power, order)),
+                _ => {}
+            }
+        }
+        let mut notation = String::new();
+        for (generator, power, order) in runs {
+            if !notation.is_empty() {
+                notation.push(' ');
+            }
+            notation.push_str(&self.move_names()[generator]);
+            let (amount, prime) = if power <= order / 2 {
+                (power, "")
+            } else {
+                (order - power, "'")
+            };
+            if amount != 1 {
+                notation.push_str(&amount.to_string());
+            }
+            notation.push_str(prime);
+        }
+        notation
+    }
+}
+
+struct Parser<'p, 'id> {
+    puzzle: &'p Puzzle<'id>,
+    notation: &'p [u8],
+    position: usize,
+    /// The order of each move, once a power of it has been read.
+    orders: Vec<Option<usize>>,
+}
+
+impl<'id> Parser<'_, 'id> {
+    /// Parses items until the end of the notation or a closing or
+    /// separating character, which is left unconsumed.
+    fn sequence(&mut self) -> Result<Word<'id>, ParseError> {
+        let mut word = Word::new();
+        loop {
+            self.skip_whitespace();
+            let start = self.position;
+            let item = match self.peek() {
+                None | Some(b')' | b']' | b':' | b',') => return Ok(word),
+                Some(b'(') => {
+                    self.position += 1;
+                    let inner = self.sequence()?;
+                    self.expect(b')', "Unclosed parenthesis", start)?;
+                    inner
+                }
+                Some(b'[') => {
+                    self.position += 1;
+                    let a = self.sequence()?;
+                    let separator = self.peek();
+                    if !matches!(separator, Some(b':' | b',')) {
+                        return Err(self.error_from("Expected ':' or ',' in brackets", start));
+                    }
+                    self.position += 1;
+                    let b = self.sequence()?;
+                    self.expect(b']', "Unclosed bracket", start)?;
+                    let conjugate = a.concat(&b).concat(&a.inverse());
+                    if separator == Some(b':') {
+                        conjugate
+                    } else {
+                        conjugate.concat(&b.inverse())
+                    }
+                }
+                Some(byte) if byte.is_ascii_alphabetic() || byte == b'_' => {
+                    while self
+                        .peek()
+                        .is_some_and(|byte| byte.is_ascii_alphabetic() || byte == b'_')
+                    {
+                        self.position += 1;
+                    }
+                    // Move names are made of ASCII bytes, so this is a
+                    // whole `str`.
+                    let name = std::str::from_utf8(&self.notation[start..self.position]).unwrap();
+                    let letter = self
+                        .puzzle
+                        .letter(name)
+                        .ok_or_else(|| self.error_from("Unknown move", start))?;
+                    [letter].into_iter().collect()
+                }
+                Some(_) => {
+                    // Cover the whole character, so the span can be used
+                    // to slice the notation.
+                    let length = match self.notation[self.position] {
+                        0xF0.. => 4,
+                        0xE0.. => 3,
+                        0xC0.. => 2,
+                        _ => 1,
+                    };
+                    return Err(self.error("Unexpected character", length));
+                }
+            };
+            let item = self.suffix(item)?;
+            word.extend(item.letters().iter().copied());
+        }
+    }
+
+    /// Applies an optional power and prime following an item, in either
+    /// order.
+    fn suffix(&mut self, item: Word<'id>) -> Result<Word<'id>, ParseError> {
+        let mut inverse = self.prime();
+        let digits = self.position;
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let mut power = 1;
+        if self.position > digits {
+            // A power at least the item's order wraps around, so there
+            // is no need to repeat the item that many times.
+            let order = self.order(&item);
+            power = std::str::from_utf8(&self.notation[digits..self.position])
+                .unwrap()
+                .parse::<usize>()
+                .map_err(|_| self.error_from("Power is too large", digits))?
+                % order;
+            if !inverse {
+                inverse = self.prime();
+            }
+        }
+        let item = if inverse { item.inverse() } else { item };
+        Ok(std::iter::repeat_n(item.letters(), power)
+            .flatten()
+            .copied()
+            .collect())
+    }
+
+    /// Consumes a `'`, if there is one next.
+    fn prime(&mut self) -> bool {
+        let prime = self.peek() == Some(b'\'');
+        if prime {
+            self.position += 1;
+        }
+        prime
+    }
+
+    /// The order of `item`'s product. A single move's order is only
+    /// computed the first time it is needed.
+    fn order(&mut self, item: &Word<'id>) -> usize {
+        let group = self.puzzle.group();
+        match item.letters() {
+            [letter] => *self.orders[letter.generator()]
+                .get_or_insert_with(|| group.base_permutations()[letter.generator()].order()),
+            _ => item.evaluate(group).order(),
+        }
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str, start: usize) -> Result<(), ParseError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error_from(message, start));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.notation.get(self.position).copied()
+    }
+
+    /// An error covering the next `length` bytes.
+    fn error(&self, message: &'static str, length: usize) -> ParseError {
+        ParseError {
+            message,
+            span: self.position..self.position + length,
+        }
+    }
+
+    /// An error covering everything from `start` to the current position.
+    fn error_from(&self, message: &'static str, start: usize) -> ParseError {
+        ParseError {
+            message,
+            span: start..self.position.max(start + 1).min(self.notation.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::puzzle::{CUBE_3X3X3, Puzzle};
+    use generativity::make_guard;
+
+    #[test]
+    fn parse_and_print() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        let [r, u] = ["R", "U"]
+            .map(|name| cube.group().base_permutations()[cube.generator(name).unwrap()].clone());
+        let sexy = r.compose(&u).compose(&r.inverse()).compose(&u.inverse());
+
+        assert_eq!(cube.parse_permutation("R U R' U'").unwrap(), sexy);
+        assert_eq!(cube.parse_permutation("[R, U]").unwrap(), sexy);
+        assert_eq!(
+            cube.parse_permutation("(R U R' U')2'").unwrap(),
+            sexy.compose(&sexy).inverse()
+        );
+        assert_eq!(
+            cube.parse_permutation("[R: U2]").unwrap(),
+            r.compose(&u).compose(&u).compose(&r.inverse())
+        );
+        assert_eq!(cube.parse_word("(R U R' U')6").unwrap().len(), 0);
+        assert_eq!(
+            cube.parse_permutation("R'2 (R U)'2").unwrap(),
+            cube.parse_permutation("R2' (R U)2'").unwrap()
+        );
+        assert_eq!(cube.parse_permutation("R'5").unwrap(), r.inverse());
+        assert!(cube.parse_word("R'2'").is_err());
+
+        let word = cube.parse_word("R R R U U F2 F' F' D4").unwrap();
+        assert_eq!(cube.to_notation(&word), "R' U2");
+
+        let error = cube.parse_word("R (U X)").unwrap_err();
+        assert_eq!(error.span, 5..6);
+        assert_eq!(cube.parse_word("R (U").unwrap_err().span, 2..4);
+        assert_eq!(cube.parse_word("R é").unwrap_err().span, 2..4);
+        assert_eq!(
+            cube.parse_word("[R U]").unwrap_err().message,
+            "Expected ':' or ',' in brackets"
+        );
+    }
+}
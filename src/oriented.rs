@@ -0,0 +1,238 @@
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use crate::puzzle::Puzzle;
+use generativity::{Guard, Id};
+use std::sync::Arc;
+
+/// How the points of a `PermGroup<'id>` are grouped into pieces: piece
+/// `i` with `k` orientations owns `k` consecutive points, one for each
+/// of its orientations, right after the points of piece `i - 1`.
+///
+/// Each layout has a brand `'layout` of its own, so the oriented
+/// permutations made from it can be composed without checking that they
+/// share their pieces.
+pub struct OrientationLayout<'id, 'layout> {
+    pieces: Arc<Pieces>,
+    point_pieces: Box<[usize]>,
+    id: Id<'id>,
+    layout: Id<'layout>,
+}
+
+/// The number of orientations of each piece and its first point, shared
+/// by a layout and every element made from it.
+#[derive(PartialEq, Eq, Hash, Debug)]
+struct Pieces {
+    moduli: Box<[u8]>,
+    offsets: Box<[usize]>,
+    length: usize,
+}
+
+/// An element of a wreath product: a permutation of pieces, together
+/// with how much each piece is twisted, modulo its number of
+/// orientations.
+///
+/// Like a `Puzzle` move, `permutation[i]` is the slot the piece in slot
+/// `i` comes from, and `orientation[i]` is how much that piece is twisted
+/// on the way. This matches the flat permutations of the group, so
+/// converting with `OrientationLayout::from_permutation` and
+/// `OrientedPermutation::to_permutation` commutes with composition.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct OrientedPermutation<'id, 'layout> {
+    permutation: Box<[usize]>,
+    orientation: Box<[u8]>,
+    pieces: Arc<Pieces>,
+    id: Id<'id>,
+    layout: Id<'layout>,
+}
+
+impl<'id, 'layout> OrientationLayout<'id, 'layout> {
+    /// Lays out pieces with `moduli[i]` orientations each over the points
+    /// of `group`, which must be exactly covered.
+    pub fn new(
+        group: &PermGroup<'id>,
+        moduli: Vec<u8>,
+        guard: Guard<'layout>,
+    ) -> Result<Self, &'static str> {
+        if moduli.contains(&0) {
+            return Err("Pieces must have at least one orientation");
+        }
+        let mut offsets = Vec::with_capacity(moduli.len());
+        let mut point_pieces = Vec::with_capacity(group.base_permutation_length);
+        for (piece, &modulus) in moduli.iter().enumerate() {
+            offsets.push(point_pieces.len());
+            point_pieces.extend(std::iter::repeat_n(piece, modulus as usize));
+        }
+        if point_pieces.len() != group.base_permutation_length {
+            return Err("Orientation layout length does not match expected length");
+        }
+        Ok(Self {
+            pieces: Arc::new(Pieces {
+                moduli: moduli.into_boxed_slice(),
+                offsets: offsets.into_boxed_slice(),
+                length: point_pieces.len(),
+            }),
+            point_pieces: point_pieces.into_boxed_slice(),
+            id: group.id,
+            layout: guard.into(),
+        })
+    }
+
+    pub fn moduli(&self) -> &[u8] {
+        &self.pieces.moduli
+    }
+
+    pub fn identity(&self) -> OrientedPermutation<'id, 'layout> {
+        let pieces = self.pieces.moduli.len();
+        OrientedPermutation {
+            permutation: (0..pieces).collect(),
+            orientation: vec![0; pieces].into_boxed_slice(),
+            pieces: Arc::clone(&self.pieces),
+            id: self.id,
+            layout: self.layout,
+        }
+    }
+
+    /// Splits a flat permutation into pieces and orientations. Fails if
+    /// it does not move the points of each piece together, as a cyclic
+    /// shift onto the points of a piece with as many orientations.
+    pub fn from_permutation(
+        &self,
+        permutation: &Permutation<'id>,
+    ) -> Result<OrientedPermutation<'id, 'layout>, &'static str> {
+        let Pieces {
+            moduli, offsets, ..
+        } = &*self.pieces;
+        let mut result = self.identity();
+        for (piece, (&offset, &modulus)) in offsets.iter().zip(moduli.iter()).enumerate() {
+            let point = permutation.0[offset];
+            let from_piece = self.point_pieces[point];
+            let twist = point - offsets[from_piece];
+            if moduli[from_piece] != modulus
+                || (0..modulus as usize).any(|orientation| {
+                    permutation.0[offset + orientation]
+                        != offsets[from_piece] + (orientation + twist) % modulus as usize
+                })
+            {
+                return Err("Permutation does not respect the orientation layout");
+            }
+            result.permutation[piece] = from_piece;
+            result.orientation[piece] = twist as u8;
+        }
+        Ok(result)
+    }
+}
+
+impl<'id, 'layout> OrientedPermutation<'id, 'layout> {
+    pub fn permutation(&self) -> &[usize] {
+        &self.permutation
+    }
+
+    pub fn orientation(&self) -> &[u8] {
+        &self.orientation
+    }
+
+    /// Composes in the same order as `Permutation::compose`: `self` is
+    /// applied first, then `b`. All three share the brand `'layout`, so
+    /// they were made from the same layout.
+    pub fn compose_into(&self, b: &Self, result: &mut Self) {
+        let moduli = &self.pieces.moduli;
+        for (i, &from) in b.permutation.iter().enumerate() {
+            result.permutation[i] = self.permutation[from];
+            result.orientation[i] = ((self.orientation[from] as u16 + b.orientation[i] as u16)
+                % moduli[from] as u16) as u8;
+        }
+    }
+
+    pub fn compose(&self, b: &Self) -> Self {
+        let mut result = self.clone();
+        self.compose_into(b, &mut result);
+        result
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut result = self.clone();
+        for (i, (&from, &twist)) in self
+            .permutation
+            .iter()
+            .zip(self.orientation.iter())
+            .enumerate()
+        {
+            result.permutation[from] = i;
+            let modulus = self.pieces.moduli[from];
+            result.orientation[from] = (modulus - twist) % modulus;
+        }
+        result
+    }
+
+    /// The flat permutation of points this element acts as.
+    pub fn to_permutation(&self) -> Permutation<'id> {
+        let Pieces {
+            moduli,
+            offsets,
+            length,
+        } = &*self.pieces;
+        let mut mapping = Vec::with_capacity(*length);
+        for (&from, &twist) in self.permutation.iter().zip(self.orientation.iter()) {
+            let modulus = moduli[from] as usize;
+            mapping.extend(
+                (0..modulus)
+                    .map(|orientation| offsets[from] + (orientation + twist as usize) % modulus),
+            );
+        }
+        Permutation(mapping.into_boxed_slice(), self.id)
+    }
+}
+
+impl<'id> Puzzle<'id> {
+    /// The layout of this puzzle's pieces over its flattened points,
+    /// branded by `guard`.
+    pub fn orientation_layout<'layout>(
+        &self,
+        guard: Guard<'layout>,
+    ) -> OrientationLayout<'id, 'layout> {
+        let moduli = self
+            .orbits()
+            .iter()
+            .flat_map(|orbit| std::iter::repeat_n(orbit.orientations() as u8, orbit.pieces()))
+            .collect();
+        OrientationLayout::new(self.group(), moduli, guard)
+            .expect("Puzzle orbits cover the points of its group")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::puzzle::{CUBE_3X3X3, Puzzle};
+    use generativity::make_guard;
+
+    #[test]
+    fn conversion_commutes_with_composition() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        make_guard!(guard);
+        let layout = cube.orientation_layout(guard);
+        let generators = cube.group().base_permutations();
+        for a in generators {
+            let oriented_a = layout.from_permutation(a).unwrap();
+            assert_eq!(&oriented_a.to_permutation(), a);
+            assert_eq!(oriented_a.compose(&oriented_a.inverse()), layout.identity());
+            for b in generators {
+                let oriented_b = layout.from_permutation(b).unwrap();
+                assert_eq!(
+                    oriented_a.compose(&oriented_b),
+                    layout.from_permutation(&a.compose(b)).unwrap()
+                );
+            }
+        }
+
+        // Another layout of the same puzzle has its own brand, so its
+        // elements don't compose with `layout`'s.
+        make_guard!(other_guard);
+        let other = cube.orientation_layout(other_guard);
+        let other_identity = other.from_permutation(&cube.group().identity()).unwrap();
+        assert_eq!(other_identity.to_permutation(), cube.group().identity());
+
+        let mut swap = cube.group().identity();
+        swap.0.swap(0, 2);
+        assert!(layout.from_permutation(&swap).is_err());
+    }
+}
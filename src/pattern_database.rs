@@ -0,0 +1,374 @@
+use crate::ida_star::Heuristic;
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use crate::moves::MoveSet;
+use crate::stabilizer_chain::Chain;
+use generativity::Id;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"PDB\0";
+
+/// Distances are stored in nibbles, so anything this far or further is
+/// stored as this value, which is still a lower bound.
+const MAX_DISTANCE: u8 = 0xF;
+
+/// The largest table `PatternDatabase::build` will allocate, or `load`
+/// will read, in entries. Two entries fit in a byte, so this is 512 MiB.
+const MAX_ENTRIES: u128 = 1 << 30;
+
+/// A pruning table for `IdaStar`: for every image a permutation can give
+/// the chosen `points`, the fewest moves that send those points back home.
+///
+/// The images are ranked through a stabilizer chain of the group whose
+/// base starts with `points`, so the table has one entry for each image
+/// the group can actually give them. Distances are nibble packed, two
+/// entries per byte.
+pub struct PatternDatabase<'id> {
+    base_permutation_length: usize,
+    points: Box<[usize]>,
+    chain: Chain,
+    fingerprint: u64,
+    table: Vec<u8>,
+    _id: Id<'id>,
+}
+
+impl<'id> PatternDatabase<'id> {
+    /// Fills the table by a breadth-first search from the identity over
+    /// `moves`.
+    pub fn build(
+        group: &PermGroup<'id>,
+        moves: &MoveSet<'id>,
+        points: Vec<usize>,
+    ) -> Result<Self, &'static str> {
+        validate_points(&points, group.base_permutation_length)?;
+        let chain = projection_chain(group, &points);
+        let entries = table_entries(&chain, points.len()).ok_or("Pattern database is too large")?;
+        let mut database = Self {
+            base_permutation_length: group.base_permutation_length,
+            fingerprint: fingerprint(group, moves, &points),
+            points: points.into_boxed_slice(),
+            chain,
+            table: vec![0xFF; entries.div_ceil(2)],
+            _id: group.id,
+        };
+
+        let mut projection = database.points.to_vec();
+        let mut layer = vec![database.chain.rank_images(&mut projection) as usize];
+        database.set(layer[0], 0);
+        let mut child = vec![0; database.points.len()];
+        for distance in 1..MAX_DISTANCE {
+            let mut next_layer = Vec::new();
+            for &rank in &layer {
+                database.chain.unrank_images(rank as u128, &mut projection);
+                for mv in moves.moves() {
+                    for (child_value, &value) in child.iter_mut().zip(&projection) {
+                        *child_value = mv.inverse_permutation().0[value];
+                    }
+                    let child_rank = database.chain.rank_images(&mut child) as usize;
+                    if database.get(child_rank) == MAX_DISTANCE {
+                        database.set(child_rank, distance);
+                        next_layer.push(child_rank);
+                    }
+                }
+            }
+            if next_layer.is_empty() {
+                break;
+            }
+            layer = next_layer;
+        }
+        Ok(database)
+    }
+
+    pub fn points(&self) -> &[usize] {
+        &self.points
+    }
+
+    /// A lower bound on the number of moves needed to bring `state` back
+    /// to the identity.
+    pub fn distance(&self, state: &Permutation<'id>) -> u8 {
+        let mut images: Vec<_> = self.points.iter().map(|&point| state.0[point]).collect();
+        self.get(self.chain.rank_images(&mut images) as usize)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a table saved by `save`, rejecting it unless it was built for
+    /// this group and these moves.
+    pub fn load(
+        path: impl AsRef<Path>,
+        group: &PermGroup<'id>,
+        moves: &MoveSet<'id>,
+    ) -> io::Result<Self> {
+        Self::read(&mut BufReader::new(File::open(path)?), group, moves)
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.base_permutation_length as u64).to_le_bytes())?;
+        writer.write_all(&self.fingerprint.to_le_bytes())?;
+        writer.write_all(&(self.points.len() as u64).to_le_bytes())?;
+        for &point in &self.points {
+            writer.write_all(&(point as u64).to_le_bytes())?;
+        }
+        writer.write_all(&(self.table.len() as u64).to_le_bytes())?;
+        writer.write_all(&self.table)
+    }
+
+    fn read(
+        reader: &mut impl Read,
+        group: &PermGroup<'id>,
+        moves: &MoveSet<'id>,
+    ) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("File is not a pattern database"));
+        }
+        if read_usize(reader)? != group.base_permutation_length {
+            return Err(invalid_data(
+                "Pattern database length does not match expected length",
+            ));
+        }
+        let stored_fingerprint = read_u64(reader)?;
+        let point_count = read_usize(reader)?;
+        if point_count > group.base_permutation_length {
+            return Err(invalid_data("Pattern database has too many points"));
+        }
+        let points = (0..point_count)
+            .map(|_| read_usize(reader))
+            .collect::<io::Result<Vec<_>>>()?;
+        validate_points(&points, group.base_permutation_length).map_err(invalid_data)?;
+        if stored_fingerprint != fingerprint(group, moves, &points) {
+            return Err(invalid_data(
+                "Pattern database was built for a different group or move set",
+            ));
+        }
+        let chain = projection_chain(group, &points);
+        let entries = table_entries(&chain, points.len())
+            .ok_or_else(|| invalid_data("Pattern database is too large"))?;
+        let table_length = read_usize(reader)?;
+        if table_length != entries.div_ceil(2) {
+            return Err(invalid_data(
+                "Pattern database table does not match its points",
+            ));
+        }
+        let mut table = vec![0; table_length];
+        reader.read_exact(&mut table)?;
+        Ok(Self {
+            base_permutation_length: group.base_permutation_length,
+            points: points.into_boxed_slice(),
+            chain,
+            fingerprint: stored_fingerprint,
+            table,
+            _id: group.id,
+        })
+    }
+
+    fn get(&self, rank: usize) -> u8 {
+        (self.table[rank / 2] >> (rank % 2 * 4)) & 0xF
+    }
+
+    fn set(&mut self, rank: usize, distance: u8) {
+        let shift = rank % 2 * 4;
+        let byte = &mut self.table[rank / 2];
+        *byte = (*byte & !(0xF << shift)) | (distance << shift);
+    }
+}
+
+impl<'id> Heuristic<'id> for PatternDatabase<'id> {
+    fn estimate(&self, state: &Permutation<'id>) -> usize {
+        self.distance(state) as usize
+    }
+}
+
+impl<'id> Heuristic<'id> for &PatternDatabase<'id> {
+    fn estimate(&self, state: &Permutation<'id>) -> usize {
+        self.distance(state) as usize
+    }
+}
+
+/// A stabilizer chain of `group` whose base starts with `points`.
+fn projection_chain(group: &PermGroup<'_>, points: &[usize]) -> Chain {
+    let n = group.base_permutation_length;
+    Chain::with_base_prefix(
+        n,
+        group.base_permutations().iter().map(|p| &*p.0),
+        points,
+        (0..n).collect(),
+    )
+}
+
+/// How many entries the table for the first `points` base points of
+/// `chain` has, unless that is more than `MAX_ENTRIES`.
+fn table_entries(chain: &Chain, points: usize) -> Option<usize> {
+    chain
+        .prefix_order(points)
+        .filter(|&entries| entries <= MAX_ENTRIES)
+        .map(|entries| entries as usize)
+}
+
+fn validate_points(points: &[usize], base_permutation_length: usize) -> Result<(), &'static str> {
+    let mut seen = vec![false; base_permutation_length];
+    for &point in points {
+        if *seen
+            .get(point)
+            .ok_or("Points contain an element greater than or equal to the length")?
+        {
+            return Err("Points contain duplicate elements");
+        }
+        seen[point] = true;
+    }
+    Ok(())
+}
+
+/// An FNV-1a hash of everything the table's distances depend on.
+fn fingerprint<'id>(group: &PermGroup<'id>, moves: &MoveSet<'id>, points: &[usize]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let values = std::iter::once(group.base_permutation_length)
+        .chain(points.iter().copied())
+        .chain(
+            moves
+                .moves()
+                .iter()
+                .flat_map(|mv| mv.permutation().0.iter().copied()),
+        );
+    for value in values {
+        for byte in (value as u64).to_le_bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_usize(reader: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(read_u64(reader)?).map_err(|_| invalid_data("Pattern database is too large"))
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ida_star::IdaStar;
+    use crate::puzzle::{CUBE_2X2X2, Puzzle};
+    use generativity::make_guard;
+    use std::collections::HashMap;
+
+    #[test]
+    fn round_trip_and_reject_other_groups() {
+        make_guard!(guard);
+        let group = PermGroup::new(
+            6,
+            vec![vec![1, 2, 3, 0, 4, 5], vec![0, 1, 5, 3, 2, 4]],
+            guard,
+        )
+        .unwrap();
+        let moves = MoveSet::new(&group, group.letters());
+        let database = PatternDatabase::build(&group, &moves, vec![0, 2]).unwrap();
+        assert_eq!(database.distance(&group.identity()), 0);
+
+        let [a, b] = group.base_permutations() else {
+            unreachable!()
+        };
+        let target = a.compose(b).compose(a);
+        let solutions = IdaStar::new(&moves, &database).solve(&target, 6);
+        assert!(database.distance(&target) as usize <= solutions[0].len());
+
+        let mut bytes = Vec::new();
+        database.write(&mut bytes).unwrap();
+        let loaded = PatternDatabase::read(&mut &bytes[..], &group, &moves).unwrap();
+        assert_eq!(loaded.table, database.table);
+
+        make_guard!(other_guard);
+        let other = PermGroup::new(6, vec![vec![1, 0, 2, 3, 4, 5]], other_guard).unwrap();
+        let other_moves = MoveSet::new(&other, other.letters());
+        assert!(PatternDatabase::read(&mut &bytes[..], &other, &other_moves).is_err());
+
+        // A header for a table far larger than `build` would make is
+        // rejected before anything is allocated.
+        make_guard!(large_guard);
+        let cycle = (1..20).chain([0]).collect();
+        let swap = [1, 0].into_iter().chain(2..20).collect();
+        let large = PermGroup::new(20, vec![cycle, swap], large_guard).unwrap();
+        let large_moves = MoveSet::new(&large, large.letters());
+        let points: Vec<_> = (0..10).collect();
+        let mut header = MAGIC.to_vec();
+        for value in [20, fingerprint(&large, &large_moves, &points), 10]
+            .into_iter()
+            .chain(0..10)
+            .chain([u64::MAX])
+        {
+            header.extend(value.to_le_bytes());
+        }
+        let error = PatternDatabase::read(&mut &header[..], &large, &large_moves)
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "Pattern database is too large");
+    }
+
+    #[test]
+    fn matches_breadth_first_search() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_2X2X2, guard).unwrap();
+        let corners = cube.orbit("CORNERS").unwrap();
+        let points: Vec<_> = (0..3).map(|c| corners.point(c, 0).unwrap()).collect();
+        let letters = ["R", "U"].map(|name| cube.letter(name).unwrap());
+        let moves = MoveSet::new(cube.group(), letters);
+        let database = PatternDatabase::build(cube.group(), &moves, points.clone()).unwrap();
+        // One entry for each image the whole group gives three stickers
+        // of different corners.
+        assert_eq!(database.table.len(), (24 * 21 * 18usize).div_ceil(2));
+
+        // Walk every element of <R, U> the way `build` walks projections.
+        let identity = cube.group().identity();
+        let mut distances = HashMap::from([(identity.0.clone(), 0)]);
+        let mut layer = vec![identity];
+        for distance in 1.. {
+            let mut next_layer = Vec::new();
+            for state in &layer {
+                for mv in moves.moves() {
+                    let child = mv.inverse_permutation().compose(state);
+                    if !distances.contains_key(&child.0) {
+                        distances.insert(child.0.clone(), distance);
+                        next_layer.push(child);
+                    }
+                }
+            }
+            if next_layer.is_empty() {
+                break;
+            }
+            layer = next_layer;
+        }
+        assert_eq!(distances.len(), 29_160);
+
+        let mut exact = HashMap::new();
+        for (mapping, &distance) in &distances {
+            let images: Vec<_> = points.iter().map(|&point| mapping[point]).collect();
+            let best = exact.entry(images).or_insert(distance);
+            *best = distance.min(*best);
+        }
+        for mapping in distances.keys() {
+            let state = Permutation(mapping.clone(), cube.group().id);
+            let images: Vec<_> = points.iter().map(|&point| mapping[point]).collect();
+            let expected = exact[&images].min(MAX_DISTANCE);
+            assert_eq!(database.distance(&state), expected);
+        }
+        let reached = (0..database.table.len() * 2)
+            .filter(|&rank| database.get(rank) != MAX_DISTANCE)
+            .count();
+        assert_eq!(reached, exact.len());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use generativity::{Guard, Id};
+
+/// Many permutations of one branded group stored back to back in a
+/// single buffer, instead of in one `Box<[usize]>` each.
+///
+/// The arena has a brand `'arena` of its own besides the group's `'id`,
+/// and since it only grows, every `ArenaPerm<'arena>` points into it.
+pub struct PermArena<'id, 'arena> {
+    base_permutation_length: usize,
+    buffer: Vec<usize>,
+    len: usize,
+    scratch: Box<[usize]>,
+    group: Id<'id>,
+    id: Id<'arena>,
+}
+
+/// A handle to a permutation stored in a `PermArena<'id, 'arena>`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ArenaPerm<'arena> {
+    index: usize,
+    id: Id<'arena>,
+}
+
+impl<'id, 'arena> PermArena<'id, 'arena> {
+    pub fn new(group: &PermGroup<'id>, guard: Guard<'arena>) -> Self {
+        Self::with_capacity(group, 0, guard)
+    }
+
+    pub fn with_capacity(group: &PermGroup<'id>, capacity: usize, guard: Guard<'arena>) -> Self {
+        Self {
+            base_permutation_length: group.base_permutation_length,
+            buffer: Vec::with_capacity(capacity * group.base_permutation_length),
+            len: 0,
+            scratch: vec![0; group.base_permutation_length].into_boxed_slice(),
+            group: group.id,
+            id: guard.into(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, permutation: &Permutation<'id>) -> ArenaPerm<'arena> {
+        self.buffer.extend_from_slice(&permutation.0);
+        self.next_handle()
+    }
+
+    pub fn get(&self, perm: ArenaPerm<'arena>) -> &[usize] {
+        let offset = self.offset(perm);
+        // SAFETY: see `offset`.
+        unsafe {
+            self.buffer
+                .get_unchecked(offset..offset + self.base_permutation_length)
+        }
+    }
+
+    pub fn to_permutation(&self, perm: ArenaPerm<'arena>) -> Permutation<'id> {
+        Permutation(self.get(perm).into(), self.group)
+    }
+
+    /// `result` may be the same handle as `a` or `b`.
+    pub fn compose_into(
+        &mut self,
+        a: ArenaPerm<'arena>,
+        b: ArenaPerm<'arena>,
+        result: ArenaPerm<'arena>,
+    ) {
+        let a = self.offset(a);
+        let b = self.offset(b);
+        let result = self.offset(result);
+        if result == a {
+            for (i, scratch_value) in self.scratch.iter_mut().enumerate() {
+                // SAFETY: see `compose_offsets_into`.
+                unsafe {
+                    *scratch_value = *self
+                        .buffer
+                        .get_unchecked(a + *self.buffer.get_unchecked(b + i));
+                }
+            }
+            // SAFETY: see `offset`.
+            unsafe {
+                self.buffer
+                    .get_unchecked_mut(result..result + self.base_permutation_length)
+                    .copy_from_slice(&self.scratch);
+            }
+        } else {
+            self.compose_offsets_into(a, b, result);
+        }
+    }
+
+    pub fn push_compose(
+        &mut self,
+        a: ArenaPerm<'arena>,
+        b: ArenaPerm<'arena>,
+    ) -> ArenaPerm<'arena> {
+        let a = self.offset(a);
+        let b = self.offset(b);
+        let result = self.buffer.len();
+        self.buffer.resize(result + self.base_permutation_length, 0);
+        self.compose_offsets_into(a, b, result);
+        self.next_handle()
+    }
+
+    /// Removes every permutation while keeping the allocation. The arena
+    /// comes back with the brand of `guard`, so handles from before the
+    /// call can't be used with it.
+    pub fn clear<'new>(mut self, guard: Guard<'new>) -> PermArena<'id, 'new> {
+        self.buffer.clear();
+        PermArena {
+            base_permutation_length: self.base_permutation_length,
+            buffer: self.buffer,
+            len: 0,
+            scratch: self.scratch,
+            group: self.group,
+            id: guard.into(),
+        }
+    }
+
+    fn next_handle(&mut self) -> ArenaPerm<'arena> {
+        let index = self.len;
+        self.len += 1;
+        ArenaPerm { index, id: self.id }
+    }
+
+    /// Handles are only made by `next_handle`, and only an arena with
+    /// the brand `'arena` makes them. That arena never shrinks, so
+    /// `perm` is in bounds.
+    fn offset(&self, perm: ArenaPerm<'arena>) -> usize {
+        perm.index * self.base_permutation_length
+    }
+
+    /// `result` must not be the same offset as `a`, since `a` would be
+    /// overwritten while it is still being read from.
+    fn compose_offsets_into(&mut self, a: usize, b: usize, result: usize) {
+        let buffer = self.buffer.as_mut_ptr();
+        for i in 0..self.base_permutation_length {
+            // SAFETY: `a`, `b`, and `result` are offsets of
+            // permutations in this arena, which all share the lifetime
+            // brand `'id`. Therefore, every value of `b` can index `a`.
+            // `result[i]` is written only after `b[i]` is read.
+            unsafe {
+                *buffer.add(result + i) = *buffer.add(a + *buffer.add(b + i));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn compose_into_aliasing_handles() {
+        make_guard!(guard);
+        let group = PermGroup::new(4, vec![vec![1, 2, 3, 0], vec![1, 0, 2, 3]], guard).unwrap();
+        let [r, s] = group.base_permutations() else {
+            unreachable!()
+        };
+        make_guard!(guard);
+        let mut arena = PermArena::new(&group, guard);
+        let a = arena.push(r);
+        let b = arena.push(s);
+
+        let ab = arena.push_compose(a, b);
+        assert_eq!(arena.get(ab), &*r.compose(s).0);
+
+        arena.compose_into(a, b, b);
+        assert_eq!(arena.get(b), &*r.compose(s).0);
+        arena.compose_into(a, a, a);
+        assert_eq!(arena.to_permutation(a), r.compose(r));
+
+        make_guard!(guard);
+        let mut arena = arena.clear(guard);
+        assert!(arena.is_empty());
+        let s = arena.push(s);
+        assert_eq!(arena.get(s), &*group.base_permutations()[1].0);
+        // `arena.get(a)` doesn't compile, since `a` has the old brand.
+    }
+}
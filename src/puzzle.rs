@@ -0,0 +1,313 @@
+//! A text format for twisty puzzle definitions, in the spirit of the
+//! KPuzzle definitions used by twizzle.
+//!
+//! A definition declares its piece orbits and then its moves:
+//!
+//! ```text
+//! # Comments run to the end of the line.
+//! orbit EDGES 12 2
+//! orbit CORNERS 8 3
+//!
+//! move U
+//!   EDGES permutation 3 0 1 2 4 5 6 7 8 9 10 11
+//!   CORNERS permutation 3 0 1 2 4 5 6 7
+//! end
+//! ```
+//!
+//! `orbit NAME PIECES ORIENTATIONS` declares an orbit of `PIECES` pieces
+//! that can each be twisted `ORIENTATIONS` ways. Within a move, the
+//! permutation line says which slot each slot's piece comes from, and
+//! the orientation line says how much each slot's piece is twisted on
+//! the way. An orbit a move leaves out is untouched, and a missing
+//! permutation or orientation line is the identity.
+//!
+//! Every orientation of every piece becomes one point of the flattened
+//! point set, so that a move can be compiled into a `PermGroup<'id>`
+//! generator.
+
+use crate::mod_5_generativity::PermGroup;
+use crate::validate_permutation;
+use crate::word::Letter;
+use generativity::Guard;
+use std::collections::HashMap;
+
+pub const CUBE_2X2X2: &str = include_str!("../puzzles/2x2x2.txt");
+pub const CUBE_3X3X3: &str = include_str!("../puzzles/3x3x3.txt");
+pub const PYRAMINX: &str = include_str!("../puzzles/pyraminx.txt");
+
+/// A set of pieces that can only move between each other's slots.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Orbit {
+    name: String,
+    pieces: usize,
+    orientations: usize,
+    offset: usize,
+}
+
+impl Orbit {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn pieces(&self) -> usize {
+        self.pieces
+    }
+
+    pub fn orientations(&self) -> usize {
+        self.orientations
+    }
+
+    /// The flattened point of the piece in slot `piece` with orientation
+    /// `orientation`.
+    pub fn point(&self, piece: usize, orientation: usize) -> Option<usize> {
+        (piece < self.pieces && orientation < self.orientations)
+            .then_some(self.offset + piece * self.orientations + orientation)
+    }
+}
+
+/// Which slot each piece of an orbit comes from and how much it is
+/// twisted on the way, or `None` where a move leaves that out.
+#[derive(Clone, Default)]
+struct Transformation {
+    permutation: Option<Vec<usize>>,
+    orientation: Option<Vec<usize>>,
+}
+
+/// A puzzle definition compiled into a `PermGroup<'id>` with one
+/// generator per move, in the order the moves are defined.
+pub struct Puzzle<'id> {
+    group: PermGroup<'id>,
+    orbits: Vec<Orbit>,
+    move_names: Vec<String>,
+    generators: HashMap<String, usize>,
+}
+
+impl<'id> Puzzle<'id> {
+    /// Compiles a definition in the format described at the top of this
+    /// module, such as `CUBE_3X3X3`.
+    pub fn parse(definition: &str, guard: Guard<'id>) -> Result<Self, &'static str> {
+        let mut orbits: Vec<Orbit> = Vec::new();
+        let mut move_names = Vec::new();
+        let mut generators = HashMap::new();
+        let mut mappings = Vec::new();
+        // How the move being defined, if any, transforms each orbit.
+        let mut current: Option<Vec<Transformation>> = None;
+        let mut base_permutation_length = 0;
+
+        for line in definition.lines() {
+            let line = line.split('#').next().unwrap();
+            let mut tokens = line.split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+            match (keyword, &mut current) {
+                ("orbit", None) => {
+                    if !move_names.is_empty() {
+                        return Err("Orbits must be declared before any move");
+                    }
+                    let name = tokens.next().ok_or("Orbit is missing its name")?;
+                    let pieces = parse_number(tokens.next())?;
+                    let orientations = parse_number(tokens.next())?;
+                    if tokens.next().is_some() {
+                        return Err("Orbit has too many fields");
+                    }
+                    if pieces == 0 || orientations == 0 {
+                        return Err("Orbit must have at least one piece and orientation");
+                    }
+                    if orientations > u8::MAX as usize {
+                        return Err("Orbit has too many orientations");
+                    }
+                    if orbits.iter().any(|orbit| orbit.name == name) {
+                        return Err("Orbit is declared twice");
+                    }
+                    let offset = base_permutation_length;
+                    base_permutation_length = pieces
+                        .checked_mul(orientations)
+                        .and_then(|points| points.checked_add(offset))
+                        .ok_or("Puzzle has too many points")?;
+                    orbits.push(Orbit {
+                        name: name.to_owned(),
+                        pieces,
+                        orientations,
+                        offset,
+                    });
+                }
+                ("move", None) => {
+                    let name = tokens.next().ok_or("Move is missing its name")?;
+                    if tokens.next().is_some() {
+                        return Err("Move has too many fields");
+                    }
+                    if generators
+                        .insert(name.to_owned(), move_names.len())
+                        .is_some()
+                    {
+                        return Err("Move is defined twice");
+                    }
+                    move_names.push(name.to_owned());
+                    current = Some(vec![Transformation::default(); orbits.len()]);
+                }
+                ("end", Some(_)) => {
+                    if tokens.next().is_some() {
+                        return Err("End has too many fields");
+                    }
+                    let transformations = current.take().unwrap();
+                    let mut mapping = vec![0; base_permutation_length];
+                    for (orbit, transformation) in orbits.iter().zip(transformations) {
+                        for piece in 0..orbit.pieces {
+                            let from_piece = transformation
+                                .permutation
+                                .as_ref()
+                                .map_or(piece, |permutation| permutation[piece]);
+                            let twist = transformation
+                                .orientation
+                                .as_ref()
+                                .map_or(0, |orientation| orientation[piece]);
+                            for o in 0..orbit.orientations {
+                                mapping[orbit.offset + piece * orbit.orientations + o] = orbit
+                                    .offset
+                                    + from_piece * orbit.orientations
+                                    + (o + twist) % orbit.orientations;
+                            }
+                        }
+                    }
+                    mappings.push(mapping);
+                }
+                ("orbit" | "move", Some(_)) => return Err("Move is missing its end"),
+                ("end", None) => return Err("End does not close a move"),
+                (orbit_name, Some(transformations)) => {
+                    let index = orbits
+                        .iter()
+                        .position(|orbit| orbit.name == orbit_name)
+                        .ok_or("Move refers to an undeclared orbit")?;
+                    let orbit = &orbits[index];
+                    let kind = tokens.next();
+                    let values = tokens
+                        .map(|token| parse_number(Some(token)))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let Transformation {
+                        permutation,
+                        orientation,
+                    } = &mut transformations[index];
+                    match kind {
+                        Some("permutation") => {
+                            if permutation.is_some() {
+                                return Err("Move gives an orbit two permutations");
+                            }
+                            validate_permutation(&values, orbit.pieces)?;
+                            *permutation = Some(values);
+                        }
+                        Some("orientation") => {
+                            if orientation.is_some() {
+                                return Err("Move gives an orbit two orientations");
+                            }
+                            if values.len() != orbit.pieces {
+                                return Err("Orientation length does not match the orbit");
+                            }
+                            if values.iter().any(|&value| value >= orbit.orientations) {
+                                return Err("Orientation is out of range for the orbit");
+                            }
+                            *orientation = Some(values);
+                        }
+                        _ => return Err("Expected a permutation or orientation line"),
+                    }
+                }
+                (_, None) => return Err("Unknown keyword"),
+            }
+        }
+        if current.is_some() {
+            return Err("Move is missing its end");
+        }
+
+        Ok(Self {
+            group: PermGroup::new(base_permutation_length, mappings, guard)?,
+            orbits,
+            move_names,
+            generators,
+        })
+    }
+
+    pub fn group(&self) -> &PermGroup<'id> {
+        &self.group
+    }
+
+    pub fn orbits(&self) -> &[Orbit] {
+        &self.orbits
+    }
+
+    pub fn orbit(&self, name: &str) -> Option<&Orbit> {
+        self.orbits.iter().find(|orbit| orbit.name == name)
+    }
+
+    /// The names of the moves, indexed by generator.
+    pub fn move_names(&self) -> &[String] {
+        &self.move_names
+    }
+
+    /// The index of the generator for the move called `name`.
+    pub fn generator(&self, name: &str) -> Option<usize> {
+        self.generators.get(name).copied()
+    }
+
+    pub fn letter(&self, name: &str) -> Option<Letter<'id>> {
+        self.group.letter(self.generator(name)?)
+    }
+}
+
+fn parse_number(token: Option<&str>) -> Result<usize, &'static str> {
+    token
+        .ok_or("Expected a number")?
+        .parse()
+        .map_err(|_| "Expected a number")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::MoveSet;
+    use crate::word::Word;
+    use generativity::make_guard;
+
+    #[test]
+    fn bundled_puzzles() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        assert_eq!(cube.group().base_permutation_length, 48);
+        assert_eq!(cube.move_names(), ["U", "L", "F", "R", "B", "D"]);
+        let [r, u] = ["R", "U"].map(|name| cube.letter(name).unwrap());
+        let sexy: Word = [r, u, r.inverse(), u.inverse()].into_iter().collect();
+        assert_eq!(sexy.evaluate(cube.group()).order(), 6);
+        let edges = cube.orbit("EDGES").unwrap();
+        let flip = cube.group().letter_permutation(cube.letter("F").unwrap());
+        assert_eq!(
+            flip.0[edges.point(1, 0).unwrap()],
+            edges.point(9, 1).unwrap()
+        );
+
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_2X2X2, guard).unwrap();
+        assert_eq!(cube.group().base_permutation_length, 24);
+
+        make_guard!(guard);
+        let pyraminx = Puzzle::parse(PYRAMINX, guard).unwrap();
+        let moves = MoveSet::new(
+            pyraminx.group(),
+            ["U", "L", "R", "B"].map(|name| pyraminx.letter(name).unwrap()),
+        );
+        assert!(moves.moves().iter().all(|mv| mv.permutation().order() == 3));
+    }
+
+    #[test]
+    fn rejects_malformed_definitions() {
+        for definition in [
+            "move U\nend\norbit A 2 1",
+            "orbit A 2 1\nmove U\n  A permutation 0 0\nend",
+            "orbit A 2 2\nmove U\n  A orientation 0 2\nend",
+            "orbit A 2 1\nmove U\n  B permutation 1 0\nend",
+            "orbit A 2 1\nmove U\n  A permutation 1 0",
+            "orbit A 2 1\norbit A 2 1",
+        ] {
+            make_guard!(guard);
+            assert!(Puzzle::parse(definition, guard).is_err());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::mod_5_generativity::PermGroup;
+use crate::word::Word;
+
+/// Shortens words over the generators of a `PermGroup<'id>` using short
+/// relators learned by composing the generators: the order of each
+/// generator (`R^4 = 1`) and which pairs of generators commute.
+pub struct Rewriter<'a, 'id> {
+    group: &'a PermGroup<'id>,
+    orders: Vec<usize>,
+    commutes: Vec<Vec<bool>>,
+}
+
+impl<'a, 'id> Rewriter<'a, 'id> {
+    pub fn new(group: &'a PermGroup<'id>) -> Self {
+        let generators = group.base_permutations();
+        Self {
+            group,
+            orders: generators.iter().map(|g| g.order()).collect(),
+            commutes: generators
+                .iter()
+                .map(|a| {
+                    generators
+                        .iter()
+                        .map(|b| a.compose(b) == b.compose(a))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// The relators this rewriter reduces with, as words that evaluate
+    /// to the identity.
+    pub fn relators(&self) -> Vec<Word<'id>> {
+        let letters: Vec<_> = self.group.letters().collect();
+        let mut relators: Vec<Word<'id>> = letters
+            .iter()
+            .zip(&self.orders)
+            .map(|(&letter, &order)| std::iter::repeat_n(letter, order).collect())
+            .collect();
+        for (i, &a) in letters.iter().enumerate() {
+            for &b in &letters[i + 1..] {
+                if self.commutes[a.generator()][b.generator()] {
+                    relators.push([a, b, a.inverse(), b.inverse()].into_iter().collect());
+                }
+            }
+        }
+        relators
+    }
+
+    /// Cancels and reorders the letters of `word` into a word that is no
+    /// longer and evaluates to the same permutation.
+    ///
+    /// Runs of one generator are reduced modulo its order, and letters
+    /// are moved past the generators they commute with so that such runs
+    /// can meet. The result is checked by evaluating both words.
+    pub fn reduce(&self, word: &Word<'id>) -> Result<Word<'id>, &'static str> {
+        let mut syllables = Vec::new();
+        for &letter in word.letters() {
+            let exponent = if letter.is_inverse() { -1 } else { 1 };
+            self.insert(&mut syllables, letter.generator(), exponent);
+        }
+        let reduced: Word<'id> = syllables
+            .into_iter()
+            .flat_map(|(generator, exponent): (usize, isize)| {
+                // `generator` came from a letter of `word`, so it is in
+                // bounds.
+                let letter = self.group.letter(generator).unwrap();
+                let letter = if exponent < 0 {
+                    letter.inverse()
+                } else {
+                    letter
+                };
+                std::iter::repeat_n(letter, exponent.unsigned_abs())
+            })
+            .collect();
+        if reduced.evaluate(self.group) != word.evaluate(self.group) {
+            return Err("Rewriting changed the permutation of the word");
+        }
+        Ok(reduced)
+    }
+
+    /// Appends `generator^exponent` to `syllables`, merging it into an
+    /// earlier syllable of the same generator if every syllable in
+    /// between commutes with it.
+    fn insert(&self, syllables: &mut Vec<(usize, isize)>, generator: usize, exponent: isize) {
+        let commutes = &self.commutes[generator];
+        let mut position = syllables.len();
+        while position > 0 && commutes[syllables[position - 1].0] {
+            if syllables[position - 1].0 == generator {
+                let (_, merged) = syllables.remove(position - 1);
+                let exponent = self.normalize(generator, merged + exponent);
+                // The neighbours of the removed syllable may now merge
+                // too, so reinsert everything after it.
+                let rest = syllables.split_off(position - 1);
+                if exponent != 0 {
+                    self.insert(syllables, generator, exponent);
+                }
+                for (generator, exponent) in rest {
+                    self.insert(syllables, generator, exponent);
+                }
+                return;
+            }
+            position -= 1;
+        }
+        // Keep each block of commuting syllables sorted by generator so
+        // that equal words end up with equal normal forms.
+        let mut sorted_position = syllables.len();
+        while sorted_position > position && syllables[sorted_position - 1].0 > generator {
+            sorted_position -= 1;
+        }
+        let exponent = self.normalize(generator, exponent);
+        if exponent != 0 {
+            syllables.insert(sorted_position, (generator, exponent));
+        }
+    }
+
+    /// Reduces `exponent` modulo the generator's order into the range
+    /// `(-order / 2, order / 2]`.
+    fn normalize(&self, generator: usize, exponent: isize) -> isize {
+        let order = self.orders[generator] as isize;
+        let exponent = exponent.rem_euclid(order);
+        if exponent > order / 2 {
+            exponent - order
+        } else {
+            exponent
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::{CUBE_3X3X3, Puzzle};
+    use generativity::make_guard;
+
+    #[test]
+    fn reduce_with_learned_relators() {
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        let rewriter = Rewriter::new(cube.group());
+        let reduce = |notation| {
+            let word = cube.parse_word(notation).unwrap();
+            cube.to_notation(&rewriter.reduce(&word).unwrap())
+        };
+
+        assert_eq!(reduce("R R R R"), "");
+        assert_eq!(reduce("R R R"), "R'");
+        assert_eq!(reduce("R U U' R'"), "");
+        assert_eq!(reduce("F R R' F'"), "");
+        assert_eq!(reduce("D U"), "U D");
+        assert_eq!(reduce("U D U'"), "D");
+        assert_eq!(reduce("R L R"), "L R2");
+        assert_eq!(reduce("R U R'"), "R U R'");
+
+        let relators = rewriter.relators();
+        // An order for each of the 6 faces and the 3 pairs of opposite
+        // faces that commute.
+        assert_eq!(relators.len(), 9);
+        for relator in &relators {
+            assert_eq!(relator.evaluate(cube.group()), cube.group().identity());
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use crate::validate_permutation;
+use generativity::Id;
+
+/// A base and strong generating set of a `PermGroup<'id>`, found with the
+/// deterministic Schreier-Sims algorithm.
+///
+/// Each level of the chain stabilizes the base points of the levels
+/// before it, and keeps an explicit transversal: for every point in the
+/// orbit of its base point, an element sending the base point there.
+pub struct StabilizerChain<'id> {
+    chain: Chain,
+    _id: Id<'id>,
+}
+
+impl<'id> PermGroup<'id> {
+    pub fn stabilizer_chain(&self) -> StabilizerChain<'id> {
+        let priority: Vec<_> = (0..self.base_permutation_length).collect();
+        self.chain_with_priority(priority)
+    }
+
+    /// Like `stabilizer_chain`, but whenever a new base point is needed,
+    /// takes the first point in `priority` that is not fixed.
+    pub fn stabilizer_chain_with_base_priority(
+        &self,
+        priority: Vec<usize>,
+    ) -> Result<StabilizerChain<'id>, &'static str> {
+        validate_permutation(&priority, self.base_permutation_length)?;
+        Ok(self.chain_with_priority(priority))
+    }
+
+    /// The number of elements in this group, or `None` if it does not
+    /// fit in a `u128`.
+    pub fn order(&self) -> Option<u128> {
+        self.stabilizer_chain().order()
+    }
+
+    fn chain_with_priority(&self, priority: Vec<usize>) -> StabilizerChain<'id> {
+        StabilizerChain {
+            chain: Chain::new(
+                self.base_permutation_length,
+                self.base_permutations.iter().map(|p| &*p.0),
+                priority,
+            ),
+            _id: self.id,
+        }
+    }
+}
+
+impl<'id> StabilizerChain<'id> {
+    pub fn base(&self) -> Vec<usize> {
+        self.chain.base()
+    }
+
+    /// The number of elements in the group, or `None` if it does not fit
+    /// in a `u128`.
+    pub fn order(&self) -> Option<u128> {
+        self.chain.order()
+    }
+
+    /// Whether `mapping` is a permutation in the group.
+    pub fn contains(&self, mapping: &[usize]) -> bool {
+        validate_permutation(mapping, self.chain.base_permutation_length).is_ok()
+            && self.chain.contains(mapping)
+    }
+
+    /// Like `Permutation::from_mapping_and_group`, but checks membership
+    /// by sifting through this chain.
+    pub fn permutation(&self, mapping: Vec<usize>) -> Result<Permutation<'id>, &'static str> {
+        validate_permutation(&mapping, self.chain.base_permutation_length)?;
+        if !self.chain.contains(&mapping) {
+            return Err("Permutation is not a member of the group");
+        }
+        Ok(Permutation(mapping.into_boxed_slice(), self._id))
+    }
+}
+
+/// The unbranded stabilizer chain behind `StabilizerChain<'id>`, for
+/// groups that are built internally rather than by a user.
+///
+/// Mappings send each point to its image, and products are written in
+/// the order they are applied.
+pub(crate) struct Chain {
+    base_permutation_length: usize,
+    priority: Vec<usize>,
+    pub(crate) levels: Vec<Level>,
+}
+
+/// A transversal element and its inverse.
+type Coset = (Box<[usize]>, Box<[usize]>);
+
+pub(crate) struct Level {
+    pub(crate) base_point: usize,
+    /// Generators of the elements fixing the base points of the levels
+    /// before this one.
+    pub(crate) generators: Vec<Box<[usize]>>,
+    orbit: Vec<usize>,
+    /// For every point, its index in `orbit`, or `usize::MAX` if it is
+    /// not in the orbit.
+    positions: Vec<usize>,
+    /// For every point in the orbit, an element sending the base point
+    /// to it and that element's inverse.
+    pub(crate) transversal: Vec<Option<Coset>>,
+    /// Every Schreier generator from the first `checked.0` orbit points
+    /// and first `checked.1` generators is known to sift.
+    checked: (usize, usize),
+}
+
+impl Chain {
+    /// Builds a chain for the group generated by `generators`, which must
+    /// be permutations of `0..base_permutation_length`. `priority` must
+    /// list every point.
+    pub(crate) fn new<'a>(
+        base_permutation_length: usize,
+        generators: impl IntoIterator<Item = &'a [usize]>,
+        priority: Vec<usize>,
+    ) -> Self {
+        Self::with_base_prefix(base_permutation_length, generators, &[], priority)
+    }
+
+    /// Like `new`, but the base starts with `prefix`, even where the
+    /// group fixes those points. The levels after the prefix are then
+    /// generated by the elements fixing every point in it.
+    pub(crate) fn with_base_prefix<'a>(
+        base_permutation_length: usize,
+        generators: impl IntoIterator<Item = &'a [usize]>,
+        prefix: &[usize],
+        priority: Vec<usize>,
+    ) -> Self {
+        let mut chain = Self {
+            base_permutation_length,
+            priority,
+            levels: Vec::new(),
+        };
+        for &base_point in prefix {
+            chain.push_level(base_point);
+        }
+        for generator in generators {
+            let (residue, level) = chain.sift(generator.into(), 0);
+            if !is_identity(&residue) {
+                chain.insert(residue, 0, level);
+            }
+        }
+
+        // Work from the last level down, and whenever a Schreier
+        // generator fails to sift, add what is left of it to the levels
+        // it belongs to and resume from the deepest of them.
+        let mut level = chain.levels.len();
+        while level > 0 {
+            match chain.unsifted_schreier_generator(level - 1) {
+                Some((residue, dropout)) => {
+                    chain.insert(residue, level, dropout);
+                    level = dropout + 1;
+                }
+                None => level -= 1,
+            }
+        }
+        chain
+    }
+
+    pub(crate) fn base(&self) -> Vec<usize> {
+        self.levels.iter().map(|level| level.base_point).collect()
+    }
+
+    pub(crate) fn order(&self) -> Option<u128> {
+        self.prefix_order(self.levels.len())
+    }
+
+    /// How many different images the first `levels` base points have,
+    /// the product of those levels' orbit sizes.
+    pub(crate) fn prefix_order(&self, levels: usize) -> Option<u128> {
+        self.levels[..levels]
+            .iter()
+            .try_fold(1u128, |order, level| {
+                order.checked_mul(level.orbit.len() as u128)
+            })
+    }
+
+    pub(crate) fn contains(&self, mapping: &[usize]) -> bool {
+        let (residue, _) = self.sift(mapping.into(), 0);
+        is_identity(&residue)
+    }
+
+    /// The position of `mapping` among the elements of the group, from 0
+    /// up to the group's order, or `None` if it is not in the group.
+    ///
+    /// Each level contributes the position in its orbit of the image of
+    /// its base point, as a digit with the first level most significant.
+    pub(crate) fn rank(&self, mapping: &[usize]) -> Option<u128> {
+        self.contains(mapping).then(|| self.rank_member(mapping))
+    }
+
+    /// Like `rank`, for a mapping known to be in the group. Only the
+    /// images of the base points are divided, not the whole mapping.
+    pub(crate) fn rank_member(&self, mapping: &[usize]) -> u128 {
+        let mut images: Vec<_> = self
+            .levels
+            .iter()
+            .map(|level| mapping[level.base_point])
+            .collect();
+        self.rank_images(&mut images)
+    }
+
+    /// The position of the images some member of the group gives the
+    /// first `images.len()` base points, from 0 up to their
+    /// `prefix_order`. `images` is overwritten.
+    pub(crate) fn rank_images(&self, images: &mut [usize]) -> u128 {
+        let mut rank = 0u128;
+        for (index, level) in self.levels[..images.len()].iter().enumerate() {
+            let point = images[index];
+            rank = rank * level.orbit.len() as u128 + level.positions[point] as u128;
+            let (_, inverse) = level.transversal[point].as_ref().unwrap();
+            for image in &mut images[index + 1..] {
+                *image = inverse[*image];
+            }
+        }
+        rank
+    }
+
+    /// The inverse of `rank`, writing into `mapping`.
+    pub(crate) fn unrank(&self, mut rank: u128, mapping: &mut [usize]) {
+        for (point, image) in mapping.iter_mut().enumerate() {
+            *image = point;
+        }
+        for level in self.levels.iter().rev() {
+            let radix = level.orbit.len() as u128;
+            let point = level.orbit[(rank % radix) as usize];
+            rank /= radix;
+            let (transversal, _) = level.transversal[point].as_ref().unwrap();
+            for image in mapping.iter_mut() {
+                *image = transversal[*image];
+            }
+        }
+    }
+
+    /// The inverse of `rank_images`, writing into `images`.
+    pub(crate) fn unrank_images(&self, mut rank: u128, images: &mut [usize]) {
+        for (image, level) in images.iter_mut().zip(&self.levels) {
+            *image = level.base_point;
+        }
+        for level in self.levels[..images.len()].iter().rev() {
+            let radix = level.orbit.len() as u128;
+            let point = level.orbit[(rank % radix) as usize];
+            rank /= radix;
+            let (transversal, _) = level.transversal[point].as_ref().unwrap();
+            for image in images.iter_mut() {
+                *image = transversal[*image];
+            }
+        }
+    }
+
+    /// Divides `element` by transversal elements from `from` on, until
+    /// it reaches a level whose orbit does not contain the image of the
+    /// base point. Returns what is left and that level, or the number of
+    /// levels if it got through all of them.
+    pub(crate) fn sift(&self, mut element: Box<[usize]>, from: usize) -> (Box<[usize]>, usize) {
+        for (index, level) in self.levels.iter().enumerate().skip(from) {
+            let Some((_, inverse)) = &level.transversal[element[level.base_point]] else {
+                return (element, index);
+            };
+            for image in element.iter_mut() {
+                *image = inverse[*image];
+            }
+        }
+        (element, self.levels.len())
+    }
+
+    /// Adds `generator`, which fixes the base points of the levels before
+    /// `to`, to the levels `from..=to`, starting a new level if `to` is
+    /// past the end.
+    fn insert(&mut self, generator: Box<[usize]>, from: usize, to: usize) {
+        if to == self.levels.len() {
+            let base_point = *self
+                .priority
+                .iter()
+                .find(|&&point| generator[point] != point)
+                .unwrap();
+            self.push_level(base_point);
+        }
+        for level in &mut self.levels[from..=to] {
+            level.generators.push(generator.clone());
+            level.extend_orbit();
+        }
+    }
+
+    fn push_level(&mut self, base_point: usize) {
+        let mut transversal = vec![None; self.base_permutation_length];
+        let identity: Box<[usize]> = (0..self.base_permutation_length).collect();
+        transversal[base_point] = Some((identity.clone(), identity));
+        let mut positions = vec![usize::MAX; self.base_permutation_length];
+        positions[base_point] = 0;
+        self.levels.push(Level {
+            base_point,
+            generators: Vec::new(),
+            orbit: vec![base_point],
+            positions,
+            transversal,
+            checked: (0, 0),
+        });
+    }
+
+    /// A Schreier generator of `level` that does not sift through the
+    /// levels after it, as what is left of it and where it stopped.
+    fn unsifted_schreier_generator(&mut self, level: usize) -> Option<(Box<[usize]>, usize)> {
+        let current = &self.levels[level];
+        let (checked_points, checked_generators) = current.checked;
+        for (point_index, &point) in current.orbit.iter().enumerate() {
+            let (transversal, _) = current.transversal[point].as_ref().unwrap();
+            for (generator_index, generator) in current.generators.iter().enumerate() {
+                if point_index < checked_points && generator_index < checked_generators {
+                    continue;
+                }
+                let (_, image_inverse) = current.transversal[generator[point]].as_ref().unwrap();
+                let schreier_generator = transversal
+                    .iter()
+                    .map(|&image| image_inverse[generator[image]])
+                    .collect();
+                let (residue, dropout) = self.sift(schreier_generator, level + 1);
+                if !is_identity(&residue) {
+                    return Some((residue, dropout));
+                }
+            }
+        }
+        let current = &mut self.levels[level];
+        current.checked = (current.orbit.len(), current.generators.len());
+        None
+    }
+}
+
+impl Level {
+    /// Grows the orbit and transversal to account for new generators,
+    /// keeping the transversal elements already found.
+    fn extend_orbit(&mut self) {
+        let mut index = 0;
+        while index < self.orbit.len() {
+            let point = self.orbit[index];
+            for generator in &self.generators {
+                let image = generator[point];
+                if self.transversal[image].is_some() {
+                    continue;
+                }
+                let (transversal, _) = self.transversal[point].as_ref().unwrap();
+                let element: Box<[usize]> = transversal.iter().map(|&p| generator[p]).collect();
+                let mut inverse = vec![0; element.len()].into_boxed_slice();
+                for (point, &image) in element.iter().enumerate() {
+                    inverse[image] = point;
+                }
+                self.transversal[image] = Some((element, inverse));
+                self.positions[image] = self.orbit.len();
+                self.orbit.push(image);
+            }
+            index += 1;
+        }
+    }
+}
+
+fn is_identity(mapping: &[usize]) -> bool {
+    mapping
+        .iter()
+        .enumerate()
+        .all(|(point, &image)| point == image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::{CUBE_2X2X2, CUBE_3X3X3, PYRAMINX, Puzzle};
+    use generativity::make_guard;
+
+    #[test]
+    fn orders() {
+        make_guard!(guard);
+        let symmetric = PermGroup::new(
+            20,
+            vec![
+                (1..20).chain([0]).collect(),
+                (0..20).map(|p| p ^ (p < 2) as usize).collect(),
+            ],
+            guard,
+        )
+        .unwrap();
+        assert_eq!(symmetric.order(), Some((1..=20).product()));
+        let chain = symmetric
+            .stabilizer_chain_with_base_priority((0..20).rev().collect())
+            .unwrap();
+        assert_eq!(chain.base()[0], 19);
+        assert!(chain.contains(&(0..20).rev().collect::<Vec<_>>()));
+
+        for (definition, order) in [
+            (CUBE_2X2X2, 88_179_840),
+            (CUBE_3X3X3, 43_252_003_274_489_856_000),
+            (PYRAMINX, 75_582_720),
+        ] {
+            make_guard!(guard);
+            let puzzle = Puzzle::parse(definition, guard).unwrap();
+            assert_eq!(puzzle.group().order(), Some(order));
+        }
+
+        make_guard!(guard);
+        let cube = Puzzle::parse(CUBE_3X3X3, guard).unwrap();
+        let chain = cube.group().stabilizer_chain();
+        let mut flip: Vec<_> = (0..48).collect();
+        flip.swap(0, 1);
+        assert!(!chain.contains(&flip));
+        assert!(chain.contains(&cube.parse_permutation("R U R' U'").unwrap().0));
+        assert!(chain.permutation(flip).is_err());
+    }
+}
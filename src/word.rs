@@ -0,0 +1,170 @@
+use crate::mod_5_generativity::{PermGroup, Permutation};
+use generativity::Id;
+
+/// A generator of a `PermGroup<'id>`, or the inverse of one.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Letter<'id> {
+    generator: usize,
+    inverse: bool,
+    id: Id<'id>,
+}
+
+impl<'id> Letter<'id> {
+    /// The index of this letter's generator in `base_permutations`.
+    pub fn generator(self) -> usize {
+        self.generator
+    }
+
+    pub fn is_inverse(self) -> bool {
+        self.inverse
+    }
+
+    pub fn inverse(self) -> Self {
+        Self {
+            inverse: !self.inverse,
+            ..self
+        }
+    }
+}
+
+impl<'id> PermGroup<'id> {
+    pub fn letter(&self, generator: usize) -> Option<Letter<'id>> {
+        (generator < self.base_permutations.len()).then_some(Letter {
+            generator,
+            inverse: false,
+            id: self.id,
+        })
+    }
+
+    pub fn letters(&self) -> impl Iterator<Item = Letter<'id>> {
+        (0..self.base_permutations.len()).map(|generator| Letter {
+            generator,
+            inverse: false,
+            id: self.id,
+        })
+    }
+
+    pub fn letter_permutation(&self, letter: Letter<'id>) -> &Permutation<'id> {
+        let permutations = if letter.inverse {
+            &self.base_permutation_inverses
+        } else {
+            &self.base_permutations
+        };
+        // SAFETY: `letter` has the same lifetime brand as `self`.
+        // Therefore, it was created by `PermGroup::letter` with a
+        // generator index that is in bounds.
+        unsafe { permutations.get_unchecked(letter.generator) }
+    }
+}
+
+/// A product of generators of a `PermGroup<'id>` that is only evaluated
+/// when asked for.
+///
+/// Words compose in the same order as `Permutation::compose`: the word
+/// `[a, b]` evaluates to `a.compose(b)`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Word<'id>(Vec<Letter<'id>>);
+
+impl<'id> Word<'id> {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn letters(&self) -> &[Letter<'id>] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn push(&mut self, letter: Letter<'id>) {
+        self.0.push(letter);
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.0.iter().rev().map(|letter| letter.inverse()).collect()
+    }
+
+    pub fn concat(&self, other: &Self) -> Self {
+        self.0.iter().chain(&other.0).copied().collect()
+    }
+
+    /// Cancels adjacent pairs of a letter and its inverse until none are
+    /// left.
+    pub fn simplify(&mut self) {
+        let mut reduced: Vec<Letter<'id>> = Vec::with_capacity(self.0.len());
+        for &letter in &self.0 {
+            if reduced.last() == Some(&letter.inverse()) {
+                reduced.pop();
+            } else {
+                reduced.push(letter);
+            }
+        }
+        self.0 = reduced;
+    }
+
+    /// The image of `point` under this word, found by following the
+    /// point through each letter instead of evaluating the whole word.
+    pub fn image(&self, group: &PermGroup<'id>, point: usize) -> Option<usize> {
+        (point < group.base_permutation_length).then(|| self.image_unchecked(group, point))
+    }
+
+    pub fn evaluate(&self, group: &PermGroup<'id>) -> Permutation<'id> {
+        Permutation(
+            (0..group.base_permutation_length)
+                .map(|point| self.image_unchecked(group, point))
+                .collect(),
+            group.id,
+        )
+    }
+
+    fn image_unchecked(&self, group: &PermGroup<'id>, point: usize) -> usize {
+        self.0.iter().rev().fold(point, |point, &letter| {
+            // SAFETY: `point` is in bounds, and every permutation of
+            // `group` maps points in bounds to points in bounds.
+            unsafe { *group.letter_permutation(letter).0.get_unchecked(point) }
+        })
+    }
+}
+
+impl<'id> FromIterator<Letter<'id>> for Word<'id> {
+    fn from_iter<T: IntoIterator<Item = Letter<'id>>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'id> Extend<Letter<'id>> for Word<'id> {
+    fn extend<T: IntoIterator<Item = Letter<'id>>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generativity::make_guard;
+
+    #[test]
+    fn lazy_evaluation_matches_compose() {
+        make_guard!(guard);
+        let group = PermGroup::new(4, vec![vec![1, 2, 3, 0], vec![1, 0, 2, 3]], guard).unwrap();
+        let [r, s] = group.base_permutations() else {
+            unreachable!()
+        };
+        let (a, b) = (group.letter(0).unwrap(), group.letter(1).unwrap());
+        let word: Word = [a, b, a.inverse()].into_iter().collect();
+        let expected = r.compose(s).compose(&r.inverse());
+        assert_eq!(word.evaluate(&group).0, expected.0);
+        assert_eq!(word.image(&group, 2), Some(expected.0[2]));
+        assert_eq!(word.image(&group, 4), None);
+
+        let mut cancelling = word.concat(&word.inverse());
+        cancelling.simplify();
+        assert!(cancelling.is_empty());
+    }
+}